@@ -0,0 +1,95 @@
+//! Benchmarks of the requests handling with many talks alive at the same time.
+
+use bevy::prelude::*;
+use bevy_talks::prelude::*;
+use criterion::{BenchmarkId, Criterion};
+
+/// The numbers of concurrent talks to benchmark.
+const TALKS: [usize; 4] = [10, 100, 1_000, 5_000];
+
+/// Creates an app with the given number of looping talks (`start --> a <--> b`).
+fn setup(talks: usize) -> (App, Vec<Entity>) {
+    let mut app = App::new();
+    app.add_plugins((AssetPlugin::default(), TalksPlugin));
+    app.add_systems(Startup, move |mut commands: Commands| {
+        for _ in 0..talks {
+            let builder = TalkBuilder::default().say("a").with_id("a");
+            commands.spawn_talk(builder.say("b").connect_to("a".to_string()));
+        }
+    });
+    app.update();
+
+    let talk_ents = app
+        .world
+        .query_filtered::<Entity, With<Talk>>()
+        .iter(&app.world)
+        .collect();
+    (app, talk_ents)
+}
+
+/// Advances a single talk while all the others are idle.
+fn next_one_talk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("next_one_talk");
+    for talks in TALKS {
+        let (mut app, talk_ents) = setup(talks);
+        let talk = *talk_ents.last().unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(talks), &talks, |b, _| {
+            b.iter(|| {
+                app.world.send_event(NextNodeRequest::new(talk));
+                app.update();
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Advances all the talks in the same frame.
+fn next_all_talks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("next_all_talks");
+    for talks in TALKS {
+        let (mut app, talk_ents) = setup(talks);
+        group.bench_with_input(BenchmarkId::from_parameter(talks), &talks, |b, _| {
+            b.iter(|| {
+                for talk in &talk_ents {
+                    app.world.send_event(NextNodeRequest::new(*talk));
+                }
+                app.update();
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Finds the current node of a talk, with the old scan of all the `CurrentNode`s as the baseline
+/// and the lookup in the `Talk` component used by the requests now.
+fn find_current_node(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_current_node");
+    for talks in TALKS {
+        let (mut app, talk_ents) = setup(talks);
+        let talk = *talk_ents.last().unwrap();
+        let mut currents = app
+            .world
+            .query_filtered::<(Entity, &Parent), With<CurrentNode>>();
+        group.bench_with_input(BenchmarkId::new("scan", talks), &talks, |b, _| {
+            b.iter(|| {
+                (currents.iter(&app.world))
+                    .find(|(_, parent)| parent.get() == talk)
+                    .map(|(node, _)| node)
+            });
+        });
+        let mut talk_comps = app.world.query::<&Talk>();
+        group.bench_with_input(BenchmarkId::new("talk", talks), &talks, |b, _| {
+            b.iter(|| talk_comps.get(&app.world, talk).unwrap().current_node());
+        });
+    }
+    group.finish();
+}
+
+/// Runs all the benchmarks.
+fn main() {
+    let mut c = Criterion::default().configure_from_args();
+    next_one_talk(&mut c);
+    next_all_talks(&mut c);
+    find_current_node(&mut c);
+    c.final_summary();
+}
@@ -2,15 +2,20 @@
 use bevy::{asset::LoadState, prelude::*};
 use bevy_talks::prelude::*;
 
+/// The states of the example app.
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
 enum AppState {
+    /// Waiting for the talk assets to load.
     #[default]
     LoadAssets,
+    /// The talk assets are loaded.
     Loaded,
 }
 
+/// Resource holding the handle of the talk asset.
 #[derive(Resource)]
 struct ChoiceTalkAsset {
+    /// The handle of the talk asset.
     handle: Handle<TalkData>,
 }
 
@@ -34,11 +39,13 @@ fn main() {
         .run();
 }
 
+/// Load the talk assets.
 fn load_talks(mut commands: Commands, server: Res<AssetServer>) {
     let h: Handle<TalkData> = server.load("talks/choices.talk.ron");
     commands.insert_resource(ChoiceTalkAsset { handle: h });
 }
 
+/// Move to the `Loaded` state when the talk assets are loaded.
 fn check_loading(
     server: Res<AssetServer>,
     simple_sp_asset: Res<ChoiceTalkAsset>,
@@ -50,6 +57,7 @@ fn check_loading(
     }
 }
 
+/// Spawn the dialogue graph with the given talk asset, using the builder.
 fn setup_talk(
     mut commands: Commands,
     talks: Res<Assets<TalkData>>,
@@ -64,6 +72,7 @@ fn setup_talk(
     println!("-----------------------------------------");
 }
 
+/// Advance the talk when the space key is pressed.
 fn interact(
     input: Res<Input<KeyCode>>,
     mut next_action_events: EventWriter<NextNodeRequest>,
@@ -92,12 +101,14 @@ fn interact(
     }
 }
 
+/// Print the text of the text nodes.
 fn print_text(mut text_events: EventReader<TextNodeEvent>) {
     for txt_event in text_events.read() {
         println!("{}", txt_event.text);
     }
 }
 
+/// Print the choices of the choice nodes.
 fn print_choice(mut choice_events: EventReader<ChoiceNodeEvent>) {
     for choice_event in choice_events.read() {
         println!("Choices:");
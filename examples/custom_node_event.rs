@@ -4,9 +4,11 @@ use std::vec;
 use bevy::prelude::*;
 use bevy_talks::prelude::*;
 
+/// A custom node component that emits a `DanceStartEvent` when reached.
 #[derive(Component, Reflect, NodeEventEmitter, Default)]
 #[reflect(Component)]
 struct DanceStart {
+    /// The dance moves.
     pub moves: Vec<String>,
 }
 
@@ -57,6 +59,7 @@ fn interact(
     }
 }
 
+/// Print the text of the text nodes.
 fn print_text(mut text_events: EventReader<TextNodeEvent>) {
     for txt_ev in text_events.read() {
         let mut speaker = "Narrator";
@@ -68,6 +71,7 @@ fn print_text(mut text_events: EventReader<TextNodeEvent>) {
     }
 }
 
+/// Print the dance moves when the dance starts.
 fn react_to_dancing(mut dance_events: EventReader<DanceStartEvent>) {
     for dance in dance_events.read() {
         println!("He: {:?}", dance.moves);
@@ -2,15 +2,20 @@
 use bevy::{asset::LoadState, prelude::*};
 use bevy_talks::prelude::*;
 
+/// The states of the example app.
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
 enum AppState {
+    /// Waiting for the talk assets to load.
     #[default]
     LoadAssets,
+    /// The talk assets are loaded.
     Loaded,
 }
 
+/// Resource holding the handle of the talk asset.
 #[derive(Resource)]
 struct FullTalkAsset {
+    /// The handle of the talk asset.
     handle: Handle<TalkData>,
 }
 
@@ -36,11 +41,13 @@ fn main() {
         .run();
 }
 
+/// Load the talk assets.
 fn load_talks(mut commands: Commands, server: Res<AssetServer>) {
     let h: Handle<TalkData> = server.load("talks/full.talk.ron");
     commands.insert_resource(FullTalkAsset { handle: h });
 }
 
+/// Move to the `Loaded` state when the talk assets are loaded.
 fn check_loading(
     server: Res<AssetServer>,
     full_talk_asset: Res<FullTalkAsset>,
@@ -52,6 +59,7 @@ fn check_loading(
     }
 }
 
+/// Spawn the dialogue graph with the given talk asset, using the builder.
 fn setup_talk(
     mut commands: Commands,
     talks: Res<Assets<TalkData>>,
@@ -94,6 +102,7 @@ fn interact(
     }
 }
 
+/// Print the text of the text nodes.
 fn print_text(mut text_events: EventReader<TextNodeEvent>) {
     for txt_ev in text_events.read() {
         let mut speaker = "Narrator";
@@ -105,18 +114,21 @@ fn print_text(mut text_events: EventReader<TextNodeEvent>) {
     }
 }
 
+/// Print the actors joining the scene.
 fn print_join(mut join_events: EventReader<JoinNodeEvent>) {
     for join_event in join_events.read() {
         println!("--- {:?} enters the scene.", join_event.actors);
     }
 }
 
+/// Print the actors leaving the scene.
 fn print_leave(mut leave_events: EventReader<LeaveNodeEvent>) {
     for leave_event in leave_events.read() {
         println!("--- {:?} exit the scene.", leave_event.actors);
     }
 }
 
+/// Print the choices of the choice nodes.
 fn print_choice(mut choice_events: EventReader<ChoiceNodeEvent>) {
     for choice_event in choice_events.read() {
         println!("Choices:");
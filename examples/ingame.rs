@@ -1,29 +1,39 @@
 //! This example shows multiple dialogue graphs attached to different entities.
-use bevy::{asset::LoadState, prelude::*};
+use bevy::{asset::RecursiveDependencyLoadState, prelude::*};
 use bevy_talks::{events::requests::NextNodeRequest, prelude::*};
 
+/// Marks whether a talk is the one the player is interacting with.
 #[derive(Component, Default)]
 struct ActiveTalk(bool);
 
+/// The states of the example app.
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
 enum AppState {
+    /// Waiting for the talk assets to load.
     #[default]
     LoadAssets,
+    /// The talk assets are loaded.
     Loaded,
 }
 
+/// Resource holding the handles of the talk assets.
 #[derive(Resource)]
 struct TalkAsset {
+    /// The handle of the talk with Bev.
     bev_talk_handle: Handle<TalkData>,
+    /// The handle of the talk with Feri.
     feri_talk_handle: Handle<TalkData>,
 }
 
+/// Marker component for the player.
 #[derive(Component)]
 struct Player;
 
+/// Marker component for the characters the player can talk to.
 #[derive(Component)]
 struct Interactable;
 
+/// Marker component for the dialogue text.
 #[derive(Component)]
 struct Dialogue;
 
@@ -54,6 +64,7 @@ fn main() {
         .run();
 }
 
+/// Load the talk assets.
 fn load_talks(mut commands: Commands, server: Res<AssetServer>) {
     let sp_a: Handle<TalkData> = server.load("talks/interact_a.talk.ron");
     let sp_b: Handle<TalkData> = server.load("talks/interact_b.talk.ron");
@@ -63,18 +74,23 @@ fn load_talks(mut commands: Commands, server: Res<AssetServer>) {
     });
 }
 
+/// Move to the `Loaded` state when the talk assets are loaded.
 fn check_loading(
     server: Res<AssetServer>,
     sp_asset: Res<TalkAsset>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
-    let load_state_a = server.get_load_state(&sp_asset.bev_talk_handle).unwrap();
-    let load_state_b = server.get_load_state(&sp_asset.feri_talk_handle).unwrap();
-    if load_state_a == LoadState::Loaded && load_state_b == LoadState::Loaded {
+    // the portraits of the actors are loaded as dependencies of the talks
+    let load_state_a = server.get_recursive_dependency_load_state(&sp_asset.bev_talk_handle);
+    let load_state_b = server.get_recursive_dependency_load_state(&sp_asset.feri_talk_handle);
+    if load_state_a == Some(RecursiveDependencyLoadState::Loaded)
+        && load_state_b == Some(RecursiveDependencyLoadState::Loaded)
+    {
         next_state.set(AppState::Loaded);
     }
 }
 
+/// Spawn the camera, the player, the characters and their talks.
 fn setup(
     mut commands: Commands,
     assets: Res<AssetServer>,
@@ -94,8 +110,8 @@ fn setup(
         },
     ));
 
-    let bev: Handle<Image> = assets.load("images/bev.png");
     let bev_talk_data = raws.get(&talk_asset.bev_talk_handle).unwrap();
+    let bev: Handle<Image> = assets.load(portrait(bev_talk_data));
     let bev_talk_builder = Talk::builder().fill_with_talk_data(bev_talk_data);
 
     let talk_graph_ent = commands
@@ -115,8 +131,8 @@ fn setup(
         ))
         .add_child(talk_graph_ent);
 
-    let feri: Handle<Image> = assets.load("images/feri.png");
     let feri_talk_data = raws.get(&talk_asset.feri_talk_handle).unwrap();
+    let feri: Handle<Image> = assets.load(portrait(feri_talk_data));
     let feri_talk_builder = Talk::builder().fill_with_talk_data(feri_talk_data);
 
     let talk_graph_ent = commands
@@ -161,6 +177,12 @@ fn setup(
     ));
 }
 
+/// The portrait of the first actor of the talk, declared in the talk file.
+fn portrait(talk_data: &TalkData) -> String {
+    talk_data.actors()[0].portrait.clone().unwrap()
+}
+
+/// Move the player with the arrow keys.
 fn move_player(
     input: Res<Input<KeyCode>>,
     t: Res<Time>,
@@ -176,6 +198,7 @@ fn move_player(
     }
 }
 
+/// Advance the active talk when the space key is pressed.
 fn advance_convo(
     input: Res<Input<KeyCode>>,
     mut next_action_events: EventWriter<NextNodeRequest>,
@@ -190,6 +213,7 @@ fn advance_convo(
     }
 }
 
+/// Advance the talk when the space key is pressed.
 fn interact(
     input: Res<Input<KeyCode>>,
     player_query: Query<&Transform, With<Player>>,
@@ -218,6 +242,7 @@ fn interact(
     }
 }
 
+/// Deactivate the talks when the player walks away.
 fn deactive_talk_when_far(
     player_query: Query<&Transform, With<Player>>,
     characters: Query<(&Transform, &Children), With<Interactable>>,
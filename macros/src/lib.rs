@@ -13,6 +13,7 @@ pub fn derive_node_event_emitter(input: TokenStream) -> TokenStream {
         syn::Data::Struct(data_struct) => match data_struct.fields {
             syn::Fields::Unit => {
                 quote! {
+                    /// The event emitted by the component.
                     #[derive(Event, Reflect, Default, Clone)]
                     #[reflect(Event)]
                     pub struct #event_struct_name;
@@ -28,14 +29,22 @@ pub fn derive_node_event_emitter(input: TokenStream) -> TokenStream {
                 let field_names: Vec<Ident> =
                     fs.named.iter().map(|f| f.ident.clone().unwrap()).collect();
                 let field_types: Vec<&syn::Type> = fs.named.iter().map(|f| &f.ty).collect();
+                let field_docs: Vec<String> = field_names
+                    .iter()
+                    .map(|f| format!("The `{f}` field of the component."))
+                    .collect();
 
                 quote! {
                     /// The event emitted by the component.
                     #[derive(Event, Reflect, Default, Clone)]
                     #[reflect(Event)]
                     pub struct #event_struct_name {
+                        /// The actor names from the node.
                         actors: Vec<String>,
-                        #( #field_names: #field_types, )*
+                        #(
+                            #[doc = #field_docs]
+                            #field_names: #field_types,
+                        )*
                     }
 
                     impl NodeEventEmitter for #struct_name {
@@ -95,7 +104,7 @@ mod tests {
         let boxed_event = named.make(&[]);
         assert!(boxed_event.is::<TestNamedEvent>());
         let event = boxed_event.downcast_ref::<TestNamedEvent>().unwrap();
-        assert_eq!(event.field1, true);
+        assert!(event.field1);
         assert_eq!(event.field2, 42);
     }
 
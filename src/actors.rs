@@ -1,9 +1,6 @@
 //! Main actor types
 
-use bevy::{
-    ecs::{bundle::Bundle, component::Component},
-    reflect::Reflect,
-};
+use bevy::{ecs::component::Component, reflect::Reflect};
 
 /// A unique identifier for an actor in a Talk.
 ///
@@ -13,12 +10,20 @@ use bevy::{
 pub(crate) type ActorSlug = String;
 
 /// The actor component for the actor entities in a Talk.
-#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq)]
+///
+/// The portrait and the voice are asset paths. When the actor comes from a talk file, they are loaded
+/// as dependencies of the `TalkData` asset, so you can get their handles with `asset_server.load(path)`
+/// without loading them twice.
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
 pub struct Actor {
     /// The name of the character that the actor plays.
     pub name: String,
     /// The unique slug of the character that the actor plays.
     pub slug: ActorSlug,
+    /// The path of the portrait image of the actor.
+    pub portrait: Option<String>,
+    /// The path of the voice (or blip) sound of the actor.
+    pub voice: Option<String>,
 }
 
 impl Actor {
@@ -27,13 +32,24 @@ impl Actor {
         Self {
             name: name.into(),
             slug: slug.into(),
+            ..Default::default()
         }
     }
-}
 
-/// A bundle that contains the components needed to make an entity an actor.
-#[derive(Bundle)]
-pub(crate) struct ActorBundle {
-    /// The actor component.
-    actor: Actor,
+    /// Sets the path of the portrait image of the actor.
+    pub fn with_portrait(mut self, path: impl Into<String>) -> Self {
+        self.portrait = Some(path.into());
+        self
+    }
+
+    /// Sets the path of the voice sound of the actor.
+    pub fn with_voice(mut self, path: impl Into<String>) -> Self {
+        self.voice = Some(path.into());
+        self
+    }
+
+    /// The paths of the assets of the actor, the portrait and the voice.
+    pub(crate) fn asset_paths(&self) -> impl Iterator<Item = &String> {
+        self.portrait.iter().chain(self.voice.iter())
+    }
 }
@@ -1,10 +1,20 @@
 //! The Bevy Command to spawn Talk entity graphs
 
 use aery::prelude::*;
-use bevy::{ecs::system::Command, prelude::*, utils::hashbrown::HashMap};
+use bevy::{
+    ecs::system::Command,
+    prelude::*,
+    reflect::TypeRegistry,
+    utils::{hashbrown::HashMap, HashSet},
+};
 
-use crate::prelude::{
-    ActorSlug, Choice, ChoiceNode, CurrentNode, EndNode, FollowedBy, PerformedBy, StartNode,
+use crate::{
+    prelude::{
+        ActorSlug, BranchNode, BuildError, Choice, ChoiceNode, ConditionalBranch, CurrentNode,
+        EndNode, EntryPoint, FollowedBy, GlobalTalkVariables, PerformedBy, StartNode, Talk,
+        TalkConditions, TalkHistory, TalkVariables, TalksSettings,
+    },
+    variables::{IncrementNode, SetNode, GLOBAL_PREFIX},
 };
 
 use super::*;
@@ -16,6 +26,8 @@ pub struct BuildTalkCommand {
     pub(crate) parent: Entity,
     /// The builder that contains the queue of nodes to spawn.
     pub(crate) builder: TalkBuilder,
+    /// The entry point to start the talk at. The default start node if `None`.
+    pub(crate) entry: Option<String>,
 }
 
 impl BuildTalkCommand {
@@ -25,28 +37,105 @@ impl BuildTalkCommand {
         Self {
             parent: p,
             builder: b,
+            entry: None,
         }
     }
+
+    /// Start the talk at the given entry point instead of the default start node.
+    pub(crate) fn at(mut self, entry: impl Into<String>) -> Self {
+        self.entry = Some(entry.into());
+        self
+    }
 }
 
 impl Command for BuildTalkCommand {
     fn apply(self, world: &mut World) {
-        // spawn the start node with all the start events
-        let start = &world.spawn((StartNode, CurrentNode)).id();
+        // check the builder before spawning anything, so a bad builder doesn't leave half a graph
+        let checked = check_builder(
+            &self.builder,
+            self.entry.as_deref(),
+            &world.resource::<AppTypeRegistry>().read(),
+        );
+        if let Err(err) = checked {
+            error!("Cannot build the talk {:?}: {err}", self.parent);
+            return;
+        }
+
+        // the unknown conditions are reported once here instead of each time they are checked
+        let deny_unknown = (world.get_resource::<TalksSettings>())
+            .is_some_and(|settings| settings.deny_unknown_conditions);
+        for condition in unknown_conditions(&self.builder, self.parent, world) {
+            let err = BuildError::UnknownCondition(condition);
+            if deny_unknown {
+                error!("Cannot build the talk {:?}: {err}", self.parent);
+                return;
+            }
+            warn!("Talk {:?}: {err}", self.parent);
+        }
+
+        // the talk variables live in the parent entity
+        if !world.entity(self.parent).contains::<TalkVariables>() {
+            world
+                .entity_mut(self.parent)
+                .insert(TalkVariables::default());
+        }
+        // the history is about the nodes built now
+        world.entity_mut(self.parent).insert(TalkHistory::default());
+
+        // spawn the start nodes with all the start events, the default one and one for each entry point
+        let start = world.spawn(StartNode).id();
+        let entry_starts = (self.builder.entry_points.iter())
+            .map(|(name, _)| world.spawn((StartNode, EntryPoint(name.clone()))).id())
+            .collect::<Vec<_>>();
+        let current = match &self.entry {
+            Some(entry) => (self.builder.entry_points.iter())
+                .position(|(name, _)| name == entry)
+                .map_or(start, |i| entry_starts[i]),
+            None => start,
+        };
+        world.entity_mut(current).insert(CurrentNode);
+        if let Some(mut talk) = world.get_mut::<Talk>(self.parent) {
+            talk.current_node = Some(current);
+            talk.start_node = Some(current);
+        }
+
+        // the queues of nodes of the talk: the main one and the ones of the entry points
+        let queues = std::iter::once((start, &self.builder))
+            .chain(
+                entry_starts
+                    .iter()
+                    .copied()
+                    .zip((self.builder.entry_points.iter()).map(|(_, builder)| builder)),
+            )
+            .collect::<Vec<_>>();
 
         // First pass: spawn all the node entities and add them to the map with their build node id
-        let (ents, mut node_entities) = spawn_dialogue_entities(&self.builder.queue, world);
+        let mut ents = vec![];
+        let mut node_entities = HashMap::new();
+        for (_, builder) in queues.iter() {
+            let (queue_ents, queue_node_entities) = spawn_dialogue_entities(&builder.queue, world);
+            ents.extend(queue_ents);
+            node_entities.extend(queue_node_entities);
+        }
         let actor_ents = spawn_actor_entities(&self.builder.actors, world);
+        insert_actor_components(&self.builder.actor_components, &actor_ents, world);
 
-        // add the start entity and all the other entities to the parent
+        // add the start entities and all the other entities to the parent
         let mut manager = world.entity_mut(self.parent);
-        manager.add_child(*start);
-        for e in ents {
+        manager.add_child(start);
+        for e in entry_starts.iter().copied().chain(ents) {
             manager.add_child(e);
         }
 
         // Second pass: Extract all the components associated with the nodes
-        let component_map = prepare_node_components(&self.builder.queue, &node_entities, world);
+        let mut component_map = HashMap::new();
+        for (_, builder) in queues.iter() {
+            component_map.extend(prepare_node_components(
+                &builder.queue,
+                &node_entities,
+                world,
+            ));
+        }
 
         // and insert them in the world
         component_map.into_iter().for_each(|(e, comps)| {
@@ -58,17 +147,152 @@ impl Command for BuildTalkCommand {
         });
 
         // Third pass: connect the entities to form the graph
-        form_graph(
-            *start,
-            &self.builder.queue,
-            self.builder.connect_parent,
-            &mut node_entities,
-            world,
-        );
+        for &(start, builder) in queues.iter() {
+            form_graph(
+                start,
+                &builder.queue,
+                builder.connect_parent.clone(),
+                &mut node_entities,
+                world,
+            );
+        }
 
         // Fourth pass: connect the actors to the nodes
-        connect_nodes_with_actors(&self.builder.queue, node_entities, actor_ents, world);
+        for (_, builder) in queues.iter() {
+            connect_nodes_with_actors(
+                &builder.queue,
+                node_entities.clone(),
+                actor_ents.clone(),
+                world,
+            );
+        }
+    }
+}
+
+/// Check that the builder can be built: the entry point to start at exists
+/// and all the components (of the entry points and the actors too) are registered.
+fn check_builder(
+    builder: &TalkBuilder,
+    entry: Option<&str>,
+    type_registry: &TypeRegistry,
+) -> Result<(), BuildError> {
+    if let Some(entry) = entry {
+        if !builder.entry_points.iter().any(|(name, _)| name == entry) {
+            return Err(BuildError::UnknownEntryPoint(entry.to_string()));
+        }
+    }
+    for (_, component) in builder.actor_components.iter() {
+        reflect_component(&**component, type_registry)?;
+    }
+    check_components(&builder.queue, type_registry)?;
+    for (_, entry_builder) in builder.entry_points.iter() {
+        check_components(&entry_builder.queue, type_registry)?;
+    }
+    let mut ids = HashSet::new();
+    check_node_ids(&builder.queue, &mut ids)?;
+    for (_, entry_builder) in builder.entry_points.iter() {
+        check_node_ids(&entry_builder.queue, &mut ids)?;
+    }
+    Ok(())
+}
+
+/// Check that the ids of the build nodes (the inner ones too) are not already in the given ids, adding them.
+fn check_node_ids<'a>(
+    build_nodes: &'a VecDeque<BuildNode>,
+    ids: &mut HashSet<&'a BuildNodeId>,
+) -> Result<(), BuildError> {
+    for build_node in build_nodes {
+        if !ids.insert(&build_node.id) {
+            return Err(BuildError::DuplicateNodeId(build_node.id.clone()));
+        }
+        for (_, _, inner_builder) in build_node.choices.iter() {
+            check_node_ids(&inner_builder.queue, ids)?;
+        }
     }
+    Ok(())
+}
+
+/// The conditions of the builder that are not registered nor a variable,
+/// either set in the talk and global variables or by a set or increment node of the builder.
+fn unknown_conditions(builder: &TalkBuilder, talk: Entity, world: &World) -> Vec<ConditionId> {
+    let mut conditions = vec![];
+    let mut set_variables = HashSet::new();
+    collect_conditions(builder, &mut conditions, &mut set_variables);
+
+    let registered = world.get_resource::<TalkConditions>();
+    let is_variable = |name: &str| match name.strip_prefix(GLOBAL_PREFIX) {
+        Some(global_name) => (world.get_resource::<GlobalTalkVariables>())
+            .is_some_and(|globals| globals.0.contains_key(global_name)),
+        None => (world.get::<TalkVariables>(talk)).is_some_and(|vars| vars.0.contains_key(name)),
+    };
+    conditions.retain(|id| {
+        !registered.is_some_and(|c| c.is_registered(id))
+            && !set_variables.contains(id.as_str())
+            && !is_variable(id)
+    });
+    conditions.into_iter().cloned().collect()
+}
+
+/// Collect the conditions of the choices and branches of the builder (without duplicates)
+/// and the variables set by its nodes, going through the inner builders and the entry points.
+fn collect_conditions<'a>(
+    builder: &'a TalkBuilder,
+    conditions: &mut Vec<&'a ConditionId>,
+    set_variables: &mut HashSet<&'a str>,
+) {
+    for build_node in builder.queue.iter() {
+        for component in build_node.components.iter() {
+            if let Some(set) = component.as_any().downcast_ref::<SetNode>() {
+                set_variables.insert(&set.name);
+            } else if let Some(increment) = component.as_any().downcast_ref::<IncrementNode>() {
+                set_variables.insert(&increment.name);
+            }
+        }
+        for (_, condition, inner_builder) in build_node.choices.iter() {
+            if let Some(condition) = condition.as_ref().filter(|c| !conditions.contains(c)) {
+                conditions.push(condition);
+            }
+            collect_conditions(inner_builder, conditions, set_variables);
+        }
+    }
+    for (_, entry_builder) in builder.entry_points.iter() {
+        collect_conditions(entry_builder, conditions, set_variables);
+    }
+}
+
+/// Check that all the components of the build nodes (the inner ones too) are registered components.
+fn check_components(
+    build_nodes: &VecDeque<BuildNode>,
+    type_registry: &TypeRegistry,
+) -> Result<(), BuildError> {
+    for build_node in build_nodes {
+        for component in build_node.components.iter() {
+            reflect_component(&**component, type_registry)?;
+        }
+        for (_, _, inner_builder) in build_node.choices.iter() {
+            check_components(&inner_builder.queue, type_registry)?;
+        }
+    }
+    Ok(())
+}
+
+/// The `ReflectComponent` of a reflected component, that can also be a dynamic value of the component type.
+///
+/// # Errors
+/// If the type of the component is not registered as a component.
+pub(crate) fn reflect_component(
+    component: &dyn Reflect,
+    type_registry: &TypeRegistry,
+) -> Result<ReflectComponent, BuildError> {
+    let type_info = component.get_represented_type_info();
+    let type_id = type_info.map_or(component.type_id(), |info| info.type_id());
+    type_registry
+        .get_type_data::<ReflectComponent>(type_id)
+        .cloned()
+        .ok_or_else(|| {
+            let type_path = type_info.map_or(component.reflect_type_path(), |i| i.type_path());
+            BuildError::UnregisteredComponent(type_path.to_string())
+        })
 }
 
 /// Extract the components from the build nodes and return a map of entity => components,
@@ -91,18 +315,9 @@ fn prepare_node_components<'a>(
                 .components
                 .iter()
                 .map(|component| {
-                    (
-                        component,
-                        type_reg
-                            .get_type_data::<ReflectComponent>((**component).type_id())
-                            .unwrap_or_else(|| {
-                                panic!(
-                                "Component {:?} not registered. Cannot build dialogue graph! :(",
-                                component
-                            )
-                            })
-                            .clone(),
-                    )
+                    let reflect_component = reflect_component(&**component, &type_reg)
+                        .expect("components checked before building");
+                    (component, reflect_component)
                 })
                 .collect::<Vec<_>>()
         };
@@ -111,7 +326,7 @@ fn prepare_node_components<'a>(
 
         // recursively insert the inner nodes
         if !build_node.choices.is_empty() {
-            for (_, inner_builder) in build_node.choices.iter() {
+            for (_, _, inner_builder) in build_node.choices.iter() {
                 let inner_comps =
                     prepare_node_components(&inner_builder.queue, node_entities, world);
                 entity_components.extend(inner_comps);
@@ -145,7 +360,7 @@ fn connect_nodes_with_actors(
 
         // recursively connect the inner nodes
         if !node.choices.is_empty() {
-            for (_, inner_builder) in node.choices.iter() {
+            for (_, _, inner_builder) in node.choices.iter() {
                 connect_nodes_with_actors(
                     &inner_builder.queue,
                     node_entities.clone(),
@@ -157,6 +372,24 @@ fn connect_nodes_with_actors(
     }
 }
 
+/// Insert the custom components of the actors in their entities.
+fn insert_actor_components(
+    actor_components: &[(ActorSlug, Box<dyn Reflect>)],
+    actor_ents: &HashMap<ActorSlug, Entity>,
+    world: &mut World,
+) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    for (slug, component) in actor_components {
+        let actor_ent = actor_ents.get(slug).unwrap_or_else(|| {
+            panic!("Error! Actor {slug} not found while adding its components.")
+        });
+        let reflect_comp = reflect_component(&**component, &type_registry)
+            .expect("components checked before building");
+        reflect_comp.insert(&mut world.entity_mut(*actor_ent), &**component);
+    }
+}
+
 /// Spawn the actor entities in the world and return a map of actor slug => entity.
 /// If the actor is already present in the world (identified via the slug), it will not be spawned again.
 fn spawn_actor_entities(actors: &[Actor], world: &mut World) -> HashMap<ActorSlug, Entity> {
@@ -191,6 +424,7 @@ fn spawn_actor_entities(actors: &[Actor], world: &mut World) -> HashMap<ActorSlu
 
 /// A recursive function that spawns all the nodes from a talk builder and adds them in the given hashmap.
 /// It is used as the first pass of the building, so we have all the entities spawned and the `build_node_entities` map filled.
+/// The entities are spawned with their `NodeId`.
 fn spawn_dialogue_entities(
     build_nodes: &VecDeque<BuildNode>,
     world: &mut World,
@@ -198,11 +432,11 @@ fn spawn_dialogue_entities(
     let mut entities: Vec<Entity> = Vec::with_capacity(build_nodes.len());
     let mut build_node_entities = HashMap::new();
     for n in build_nodes.iter() {
-        let e = world.spawn_empty().id();
+        let e = world.spawn(NodeId(n.id.clone())).id();
         entities.push(e);
         build_node_entities.insert(n.id.clone(), e);
 
-        for (_, inner_builder) in n.choices.iter() {
+        for (_, _, inner_builder) in n.choices.iter() {
             let (inner_ents, inner_bne) = spawn_dialogue_entities(&inner_builder.queue, world);
             entities.extend(inner_ents);
             build_node_entities.extend(inner_bne);
@@ -279,7 +513,7 @@ fn form_graph(
             // We have to process the branches from the inner builders
             // and connect them to the choice node
             let mut choices: Vec<Choice> = Vec::with_capacity(build_node.choices.len());
-            for (choice_text, inner_builder) in build_node.choices.iter() {
+            for (choice_text, condition, inner_builder) in build_node.choices.iter() {
                 // recursively spawn the branches
                 let (branch_root, branch_leaves) = form_graph(
                     this_ent,
@@ -288,12 +522,28 @@ fn form_graph(
                     node_entities,
                     world,
                 );
-                choices.push(Choice::new(choice_text, branch_root));
+                choices.push(Choice {
+                    text: choice_text.clone(),
+                    next: branch_root,
+                    condition: condition.clone(),
+                    seen: false,
+                });
                 leaves.extend(branch_leaves);
             }
 
-            // insert the ChoiceNode component here
-            world.entity_mut(this_ent).insert(ChoiceNode(choices));
+            // insert the ChoiceNode (or BranchNode) component here
+            if build_node.branching {
+                let branches = choices
+                    .into_iter()
+                    .map(|c| ConditionalBranch {
+                        condition: c.condition,
+                        next: c.next,
+                    })
+                    .collect();
+                world.entity_mut(this_ent).insert(BranchNode(branches));
+            } else {
+                world.entity_mut(this_ent).insert(ChoiceNode(choices));
+            }
 
             previous_node_was_choice = true;
         }
@@ -617,7 +867,7 @@ mod integration_tests {
     use rstest::{fixture, rstest};
 
     use crate::{
-        prelude::TextNode,
+        prelude::{TalkConditionsAppExt, TextNode},
         tests::{get_comp, talks_minimal_app},
     };
 
@@ -819,21 +1069,21 @@ mod integration_tests {
         // We have to use Leaf tho cause in aery Root and Leaf are swapped
         let root_nodes: Vec<_> = world
             .query::<(Entity, Leaf<FollowedBy>)>()
-            .iter(&world)
+            .iter(world)
             .collect();
         assert_eq!(root_nodes.len(), if node_number > 0 { 1 } else { 0 });
 
         // check relations (e1, e2)
         let related_nodes: Vec<_> = world
             .query::<(Entity, Relations<FollowedBy>)>()
-            .iter(&world)
+            .iter(world)
             .collect();
         assert_eq!(related_nodes.len(), expected_nodes_in_relation);
 
         // check leaf nodes
         let leaf_nodes: Vec<_> = world
             .query::<(Entity, Root<FollowedBy>)>()
-            .iter(&world)
+            .iter(world)
             .collect();
         assert_eq!(leaf_nodes.len(), expected_leaf_nodes);
     }
@@ -860,4 +1110,189 @@ mod integration_tests {
             .single(&app.world);
         get_comp::<TestComp>(ent, &mut app.world);
     }
+
+    #[test]
+    fn actor_components_are_inserted_in_the_actors() {
+        let mut app = talks_minimal_app();
+        app.register_type::<TestComp>();
+        let already_spawned = app.world.spawn(Actor::new("bob", "Bob")).id();
+        let builder = TalkBuilder::default()
+            .add_actor(Actor::new("alice", "Alice").with_portrait("alice.png"))
+            .with_actor_component("alice", TestComp)
+            .with_actor_component("bob", TestComp)
+            .actor_say("alice", "Hello");
+
+        BuildTalkCommand::new(app.world.spawn_empty().id(), builder).apply(&mut app.world);
+
+        let mut actors = app.world.query_filtered::<&Actor, With<TestComp>>();
+        assert_eq!(actors.iter(&app.world).count(), 2);
+        assert!(app.world.entity(already_spawned).contains::<TestComp>());
+    }
+
+    #[test]
+    fn unregistered_components_are_not_built() {
+        let mut app = talks_minimal_app();
+        let builder = TalkBuilder::default().say("Hello There").choose(vec![(
+            "Choice",
+            TalkBuilder::default().say("Hi").with_component(TestComp),
+        )]);
+
+        let talk = app.world.spawn_empty().id();
+        BuildTalkCommand::new(talk, builder).apply(&mut app.world);
+
+        assert!(app.world.get::<Children>(talk).is_none());
+        assert_eq!(app.world.query::<&TextNode>().iter(&app.world).count(), 0);
+
+        let err = check_components(
+            &TalkBuilder::default()
+                .empty_node()
+                .with_component(TestComp)
+                .queue,
+            &app.world.resource::<AppTypeRegistry>().read(),
+        );
+        assert_eq!(
+            err,
+            Err(BuildError::UnregisteredComponent(
+                "bevy_talks::builder::build_command::integration_tests::TestComp".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn entry_points_have_their_own_start_node() {
+        let builder = TalkBuilder::default()
+            .say("Nice to meet you!")
+            .say("What can I do for you?")
+            .with_id("hub")
+            .entry_point(
+                "after_quest",
+                TalkBuilder::default()
+                    .say("Thanks for the help!")
+                    .connect_to("hub".to_string()),
+            )
+            .entry_point(
+                "again",
+                TalkBuilder::default().connect_to("hub".to_string()),
+            );
+
+        let mut app = talks_minimal_app();
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder)
+            .at("after_quest")
+            .apply(&mut app.world);
+        let world = &mut app.world;
+
+        assert_eq!(world.query::<&StartNode>().iter(world).count(), 3);
+        assert_eq!(world.query::<&TextNode>().iter(world).count(), 3);
+
+        // the talk starts at the entry point
+        let (current, entry) = world
+            .query_filtered::<(Entity, &EntryPoint), With<CurrentNode>>()
+            .single(world);
+        assert_eq!(entry.0, "after_quest");
+        assert_eq!(world.get::<Talk>(talk).unwrap().start_node, Some(current));
+
+        let (mut edges, mut entries) = (
+            world.query::<Relations<FollowedBy>>(),
+            world.query::<(Entity, &EntryPoint)>(),
+        );
+        let default_start = world
+            .query_filtered::<Entity, (With<StartNode>, Without<EntryPoint>)>()
+            .single(world);
+        let world = &*world;
+        let mut follower = |node| edges.get(world, node).unwrap().targets(FollowedBy)[0];
+        let text = |node| &world.get::<TextNode>(node).unwrap().0;
+
+        assert_eq!(text(follower(default_start)), "Nice to meet you!");
+        let thanks = follower(current);
+        assert_eq!(text(thanks), "Thanks for the help!");
+        let hub = follower(thanks);
+        assert_eq!(text(hub), "What can I do for you?");
+        let (again, _) = entries.iter(world).find(|(_, e)| e.0 == "again").unwrap();
+        assert_eq!(follower(again), hub);
+    }
+
+    #[test]
+    fn unknown_entry_point_is_not_built() {
+        let builder = TalkBuilder::default()
+            .say("Hello")
+            .entry_point("again", TalkBuilder::default().say("Hello again"));
+        let mut app = talks_minimal_app();
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder)
+            .at("after_quest")
+            .apply(&mut app.world);
+
+        assert!(app.world.get::<Children>(talk).is_none());
+        assert_eq!(app.world.query::<&StartNode>().iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn duplicate_node_ids_are_not_built() {
+        let builder = TalkBuilder::default()
+            .say("Hello")
+            .with_id("x")
+            .entry_point(
+                "again",
+                TalkBuilder::default().say("Hello again").with_id("x"),
+            );
+        let mut app = talks_minimal_app();
+        let registry = app.world.resource::<AppTypeRegistry>().read();
+        let checked = check_builder(&builder, None, &registry);
+        assert_eq!(checked, Err(BuildError::DuplicateNodeId("x".to_string())));
+        drop(registry);
+
+        let builder = TalkBuilder::default()
+            .say("a")
+            .with_id("x")
+            .choose(vec![("Go", TalkBuilder::default().say("b").with_id("x"))]);
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder).apply(&mut app.world);
+        assert!(app.world.get::<Children>(talk).is_none());
+    }
+
+    #[test]
+    fn unknown_conditions_are_not_registered_nor_set() {
+        let mut app = talks_minimal_app();
+        app.register_talk_condition("registered", || true);
+        let globals = &mut app.world.resource_mut::<GlobalTalkVariables>().0;
+        globals.insert("flag".to_string(), true.into());
+        let talk = app.world.spawn(Talk::default()).id();
+
+        let inner = TalkBuilder::default().choose_if(vec![
+            ("E", Some("missing"), TalkBuilder::default().say("e")),
+            ("F", Some("counted"), TalkBuilder::default().say("f")),
+        ]);
+        let builder = TalkBuilder::default()
+            .choose_if(vec![
+                ("A", Some("registered"), TalkBuilder::default().say("a")),
+                ("B", Some("global.flag"), TalkBuilder::default().say("b")),
+                ("C", Some("missing"), inner),
+            ])
+            .increment("counted", 1)
+            .entry_point(
+                "later",
+                TalkBuilder::default().branch(vec![(Some("other"), TalkBuilder::default())]),
+            );
+
+        let unknown = unknown_conditions(&builder, talk, &app.world);
+        assert_eq!(unknown, ["missing", "other"]);
+    }
+
+    #[rstest]
+    #[case(false, true)]
+    #[case(true, false)]
+    fn unknown_conditions_stop_the_build_if_denied(#[case] deny: bool, #[case] built: bool) {
+        let mut app = talks_minimal_app();
+        app.world
+            .resource_mut::<TalksSettings>()
+            .deny_unknown_conditions = deny;
+        let builder = TalkBuilder::default().choose_if(vec![
+            ("A", Some("typo"), TalkBuilder::default().say("a")),
+            ("B", None, TalkBuilder::default().say("b")),
+        ]);
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder).apply(&mut app.world);
+        assert_eq!(app.world.get::<Children>(talk).is_some(), built);
+    }
 }
@@ -1,8 +1,16 @@
 //! Commands for talks
 
-use bevy::ecs::system::{Commands, EntityCommands};
+use aery::{prelude::*, tuple_traits::RelationEntries};
+use bevy::{
+    ecs::system::{Command, Commands, EntityCommands},
+    prelude::*,
+    utils::HashSet,
+};
 
-use crate::prelude::Talk;
+use crate::{
+    prelude::{PerformedBy, Talk},
+    state::{RestoreTalkCommand, TalkState},
+};
 
 use super::{build_command::BuildTalkCommand, TalkBuilder};
 
@@ -21,6 +29,68 @@ pub trait TalkCommandsExt<'w, 's> {
     ///     commands.spawn_talk(talk_builder);
     /// }
     fn spawn_talk(&mut self, builder: TalkBuilder) -> EntityCommands<'w, 's, '_>;
+
+    /// Spawns a dialogue graph like [`TalkCommandsExt::spawn_talk`], starting from the named entry point
+    /// instead of the default start node. If the builder has no such entry point the error is logged and the graph is not built.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::*;
+    /// use bevy::prelude::*;
+    ///
+    /// fn setup(mut commands: Commands) {
+    ///     let talk_builder = TalkBuilder::default()
+    ///         .say("Nice to meet you!")
+    ///         .entry_point("after_quest", TalkBuilder::default().say("Thanks for the help!"));
+    ///     commands.spawn_talk_at(talk_builder, "after_quest");
+    /// }
+    fn spawn_talk_at(
+        &mut self,
+        builder: TalkBuilder,
+        entry_point: impl Into<String>,
+    ) -> EntityCommands<'w, 's, '_>;
+
+    /// Spawns a dialogue graph like [`TalkCommandsExt::spawn_talk`] and restores the saved [`TalkState`] in it.
+    /// The builder must have the same data (and node ids) of the saved talk. Restore errors are logged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::*;
+    /// use bevy::prelude::*;
+    ///
+    /// fn load(mut commands: Commands) {
+    ///     let talk_builder = TalkBuilder::default().say("Hello").with_id("hello");
+    ///     let state = TalkState {
+    ///         current_node: Some("hello".to_string()),
+    ///         has_started: true,
+    ///         ..default()
+    ///     };
+    ///     commands.spawn_talk_with_state(talk_builder, state);
+    /// }
+    fn spawn_talk_with_state(
+        &mut self,
+        builder: TalkBuilder,
+        state: TalkState,
+    ) -> EntityCommands<'w, 's, '_>;
+
+    /// Despawns a talk: the parent entity and all its nodes, with their `FollowedBy` and `PerformedBy` relations.
+    /// The actors are kept, since they are shared by the talks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::*;
+    /// use bevy::prelude::*;
+    ///
+    /// fn cleanup(mut commands: Commands, mut end_events: EventReader<EndEvent>) {
+    ///     for ev in end_events.read() {
+    ///         commands.despawn_talk(ev.0);
+    ///     }
+    /// }
+    fn despawn_talk(&mut self, talk: Entity);
+
+    /// Despawns a talk like [`TalkCommandsExt::despawn_talk`], and the actors of the talk
+    /// that no longer perform in any node (of any talk).
+    fn despawn_talk_and_actors(&mut self, talk: Entity);
 }
 
 impl<'w, 's> TalkCommandsExt<'w, 's> for Commands<'w, 's> {
@@ -29,4 +99,173 @@ impl<'w, 's> TalkCommandsExt<'w, 's> for Commands<'w, 's> {
         self.add(BuildTalkCommand::new(parent, builder));
         self.entity(parent)
     }
+
+    fn spawn_talk_at(
+        &mut self,
+        builder: TalkBuilder,
+        entry_point: impl Into<String>,
+    ) -> EntityCommands<'w, 's, '_> {
+        let parent = self.spawn(Talk::default()).id();
+        self.add(BuildTalkCommand::new(parent, builder).at(entry_point));
+        self.entity(parent)
+    }
+
+    fn spawn_talk_with_state(
+        &mut self,
+        builder: TalkBuilder,
+        state: TalkState,
+    ) -> EntityCommands<'w, 's, '_> {
+        let parent = self.spawn(Talk::default()).id();
+        self.add(BuildTalkCommand::new(parent, builder));
+        self.add(RestoreTalkCommand {
+            talk: parent,
+            state,
+        });
+        self.entity(parent)
+    }
+
+    fn despawn_talk(&mut self, talk: Entity) {
+        self.add(DespawnTalkCommand {
+            talk,
+            despawn_actors: false,
+        });
+    }
+
+    fn despawn_talk_and_actors(&mut self, talk: Entity) {
+        self.add(DespawnTalkCommand {
+            talk,
+            despawn_actors: true,
+        });
+    }
+}
+
+/// The command that despawns a talk and its nodes, and optionally its orphaned actors.
+/// Errors are logged.
+pub(crate) struct DespawnTalkCommand {
+    /// The talk parent entity.
+    pub(crate) talk: Entity,
+    /// Whether to despawn the actors of the talk that don't perform anymore.
+    pub(crate) despawn_actors: bool,
+}
+
+impl Command for DespawnTalkCommand {
+    fn apply(self, world: &mut World) {
+        if !world
+            .get_entity(self.talk)
+            .is_some_and(|t| t.contains::<Talk>())
+        {
+            error!("Cannot despawn talk {:?}: it is not a talk", self.talk);
+            return;
+        }
+        let actors = despawn_talk_nodes(world, self.talk);
+        world.entity_mut(self.talk).despawn_recursive();
+
+        if self.despawn_actors {
+            let mut performers = world.query::<Relations<PerformedBy>>();
+            let orphans = (actors.into_iter())
+                .filter(|&a| {
+                    performers
+                        .get(world, a)
+                        .map_or(true, |r| r.hosts(PerformedBy).is_empty())
+                })
+                .collect::<Vec<_>>();
+            for actor in orphans {
+                world.entity_mut(actor).checked_despawn();
+            }
+        }
+    }
+}
+
+/// Despawns the nodes of a talk with their relations, returning the actors that performed in them.
+pub(crate) fn despawn_talk_nodes(world: &mut World, talk: Entity) -> HashSet<Entity> {
+    let nodes = world.get::<Children>(talk).map_or(vec![], |c| c.to_vec());
+    let mut performers = world.query::<Relations<PerformedBy>>();
+    let actors = (nodes.iter())
+        .filter_map(|&n| performers.get(world, n).ok())
+        .flat_map(|r| r.targets(PerformedBy).to_vec())
+        .collect();
+
+    world.entity_mut(talk).clear_children();
+    for node in nodes {
+        // despawning a node can despawn the ones before it, following the relations
+        if let Some(node) = world.get_entity_mut(node) {
+            node.checked_despawn();
+        }
+    }
+    actors
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use crate::{
+        prelude::*,
+        tests::{count, spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// Builds a talk with the given actors performing.
+    fn build_talk(app: &mut App, actors: &[&str]) -> Entity {
+        let builder = actors.iter().fold(TalkBuilder::default(), |b, &slug| {
+            b.add_actor(Actor::new(slug, slug)).actor_say(slug, "Hello")
+        });
+        let builder = builder.choose(vec![("Bye", TalkBuilder::default().say("Bye"))]);
+        spawn_built_talk(app, builder)
+    }
+
+    /// Runs the commands added by the given function.
+    fn run(world: &mut World, add: impl FnOnce(&mut Commands)) {
+        let mut queue = CommandQueue::default();
+        add(&mut Commands::new(&mut queue, world));
+        queue.apply(world);
+    }
+
+    #[test]
+    fn despawn_talk_keeps_the_actors() {
+        let mut app = talks_minimal_app();
+        let talk = build_talk(&mut app, &["bob"]);
+        let other = build_talk(&mut app, &[]);
+        let nodes_of_other = app.world.get::<Children>(other).unwrap().len();
+
+        run(&mut app.world, |c| c.despawn_talk(talk));
+
+        assert!(app.world.get_entity(talk).is_none());
+        assert_eq!(count::<&Parent>(&mut app.world), nodes_of_other);
+        assert_eq!(count::<&Actor>(&mut app.world), 1);
+        let (bob, _) = app.world.query::<(Entity, &Actor)>().single(&app.world);
+        let hosts = app
+            .world
+            .query::<Relations<PerformedBy>>()
+            .get(&app.world, bob)
+            .map_or(0, |r| r.hosts(PerformedBy).len());
+        assert_eq!(hosts, 0);
+    }
+
+    #[test]
+    fn despawn_talk_and_orphaned_actors() {
+        let mut app = talks_minimal_app();
+        app.world.spawn(Actor::new("alice", "Alice"));
+        let talk = build_talk(&mut app, &["bob", "carl"]);
+        build_talk(&mut app, &["carl"]);
+
+        run(&mut app.world, |c| c.despawn_talk_and_actors(talk));
+
+        let mut slugs = (app.world.query::<&Actor>().iter(&app.world))
+            .map(|a| a.slug.as_str())
+            .collect::<Vec<_>>();
+        slugs.sort();
+        // carl still performs in the other talk, alice is not in the talk
+        assert_eq!(slugs, vec!["alice", "carl"]);
+        assert_eq!(count::<&Talk>(&mut app.world), 1);
+    }
+
+    #[test]
+    fn despawn_not_a_talk() {
+        let mut app = talks_minimal_app();
+        let e = app.world.spawn_empty().id();
+        run(&mut app.world, |c| c.despawn_talk(e));
+        assert!(app.world.get_entity(e).is_some());
+    }
 }
@@ -4,7 +4,9 @@ use bevy::prelude::*;
 use bevy::utils::Uuid;
 use std::collections::VecDeque;
 
-use crate::prelude::{Actor, ActorSlug, TalkData};
+use crate::conditions::ConditionId;
+use crate::prelude::{Actor, ActorSlug, AutoAdvance, ChoiceTimeout, TalkData};
+use crate::variables::{IncrementNode, SetNode, TalkValue, VariableName};
 use crate::{JoinNode, LeaveNode, TextNode};
 
 pub mod build_command;
@@ -40,9 +42,13 @@ pub struct TalkBuilder {
     pub(crate) queue: VecDeque<BuildNode>,
     /// The queue of actors that will be spawned and connected to the nodes.
     pub(crate) actors: Vec<Actor>,
+    /// The custom components to add to the actor entities, with the slug of their actor.
+    pub(crate) actor_components: Vec<(ActorSlug, Box<dyn Reflect>)>,
     /// It is set when `connect_to` is called on an empty builder.
     /// It signals the Command to connect the last node of the parent builder (in a choice node).
     pub(crate) connect_parent: Option<BuildNodeId>,
+    /// The named entry points, with the builders of the nodes that follow their start nodes.
+    pub(crate) entry_points: Vec<(String, TalkBuilder)>,
 }
 
 /// The ID of the nodes in the builder. It is used to identify the dialogue graph nodes before
@@ -50,6 +56,14 @@ pub struct TalkBuilder {
 /// It is useful to connect manually the nodes at build time with the `connect_to` method.
 pub type BuildNodeId = String;
 
+/// The id of a dialogue node, added to all the node entities spawned by the builder.
+///
+/// It is a random uuid unless set with [`TalkBuilder::with_id`].
+/// The nodes built from talk.ron files use the id of their action.
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct NodeId(pub BuildNodeId);
+
 /// A struct with the data to build a node.
 #[derive(Default)]
 pub(crate) struct BuildNode {
@@ -59,12 +73,14 @@ pub(crate) struct BuildNode {
     /// NOTE: due to the limitation of current entity relationship system (with aery) we need to store the choices
     /// until the entities are spawned cause edges cannot hold any data, so we can't already create the
     /// choice node components.
-    pub(crate) choices: Vec<(String, TalkBuilder)>,
+    pub(crate) choices: Vec<(String, Option<ConditionId>, TalkBuilder)>,
+    /// If true, the `choices` are automatic branches (a `BranchNode`) instead of player choices (a `ChoiceNode`).
+    pub(crate) branching: bool,
     /// The ids to add extra connections.
     pub(crate) manual_connections: Vec<BuildNodeId>,
     /// The actors slugs that are performing the node action.
     pub(crate) actors: Vec<ActorSlug>,
-    /// The components to add to the node entity. These will be `TextNode`, JoinNode`, `LeaveNode` + custom components.
+    /// The components to add to the node entity. These will be `TextNode`, `JoinNode`, `LeaveNode` + custom components.
     /// `ChoiceNode` components are added later when the entities are spawned.
     pub(crate) components: Vec<Box<dyn Reflect>>,
 }
@@ -147,17 +163,113 @@ impl TalkBuilder {
     ///     ("Choice 2", TalkBuilder::default().say("World!")),
     /// ]).say("Hi");
     /// ```
-    pub fn choose(mut self, choices: Vec<(impl Into<String>, Self)>) -> Self {
-        assert!(!choices.is_empty(), "You can't choose node without choices");
+    pub fn choose(self, choices: Vec<(impl Into<String>, Self)>) -> Self {
+        let choices = choices
+            .into_iter()
+            .map(|(t, b)| (t.into(), None, b))
+            .collect();
+        self.push_choices(choices, false)
+    }
 
+    /// Add a choice node like [`TalkBuilder::choose`], where each choice can have a condition.
+    ///
+    /// A choice with a condition is offered (in the `ChoiceNodeEvent`) and can be chosen
+    /// only if the condition holds. A `None` condition means the choice is always available.
+    /// Register the conditions with `app.register_talk_condition`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// TalkBuilder::default().choose_if(vec![
+    ///     ("Open the door", Some("has_key"), TalkBuilder::default().say("It opens.")),
+    ///     ("Leave", None, TalkBuilder::default().say("Bye.")),
+    /// ]);
+    /// ```
+    pub fn choose_if(
+        self,
+        choices: Vec<(impl Into<String>, Option<impl Into<ConditionId>>, Self)>,
+    ) -> Self {
         let choices = choices
             .into_iter()
-            .map(|(t, b)| (t.into(), b))
-            .collect::<Vec<(String, TalkBuilder)>>();
+            .map(|(t, c, b)| (t.into(), c.map(Into::into), b))
+            .collect();
+        self.push_choices(choices, false)
+    }
+
+    /// Give a time limit in seconds to the choice node just added with [`TalkBuilder::choose`] or [`TalkBuilder::choose_if`].
+    /// When the time runs out the choice at the `default_choice` index is chosen.
+    /// It adds the [`ChoiceTimeout`] component to the node.
+    ///
+    /// The index counts all the choices of the node, even the ones whose condition doesn't hold.
+    /// If the condition of the default choice doesn't hold when the time runs out, nothing is chosen.
+    ///
+    /// # Panics
+    /// If the latest node added to the builder is not a choice node or the default choice index is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// TalkBuilder::default()
+    ///     .choose(vec![
+    ///         ("Save Clementine", TalkBuilder::default().say("She's safe.")),
+    ///         ("Save Duck", TalkBuilder::default().say("He's safe.")),
+    ///     ])
+    ///     .choice_timeout(5.0, 1);
+    /// ```
+    pub fn choice_timeout(self, seconds: f32, default_choice: usize) -> Self {
+        match self.queue.back() {
+            Some(node) if !node.branching && default_choice < node.choices.len() => (),
+            Some(node) if !node.branching && !node.choices.is_empty() => {
+                panic!("The default choice {default_choice} is out of range")
+            }
+            _ => panic!("You can add a timeout only to a choice node"),
+        }
+        self.with_component(ChoiceTimeout {
+            seconds,
+            default_choice,
+        })
+    }
+
+    /// Add a branch node that automatically follows the first branch whose condition holds.
+    /// A `None` condition always holds, so it can be used as the last "else" branch.
+    ///
+    /// The branch node is never the current node, it is traversed automatically when advancing the talk.
+    /// The branches converge into the successive node in the same way as with [`TalkBuilder::choose`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// TalkBuilder::default().branch(vec![
+    ///     (Some("quest_done"), TalkBuilder::default().say("Thank you!")),
+    ///     (None, TalkBuilder::default().say("Did you find it?")),
+    /// ]);
+    /// ```
+    pub fn branch(self, branches: Vec<(Option<impl Into<ConditionId>>, Self)>) -> Self {
+        let branches = branches
+            .into_iter()
+            .map(|(c, b)| (String::new(), c.map(Into::into), b))
+            .collect();
+        self.push_choices(branches, true)
+    }
+
+    /// Add a node with the given choices (or branches) to the queue.
+    fn push_choices(
+        mut self,
+        choices: Vec<(String, Option<ConditionId>, TalkBuilder)>,
+        branching: bool,
+    ) -> Self {
+        assert!(!choices.is_empty(), "You can't choose node without choices");
 
         let choice_node = BuildNode {
             id: Uuid::new_v4().to_string(),
             choices,
+            branching,
             ..default()
         };
 
@@ -189,6 +301,60 @@ impl TalkBuilder {
         self
     }
 
+    /// Add a node that sets a variable when reached. It will spawn an entity with `SetNode`.
+    ///
+    /// The node is traversed automatically: when reached the variable is set and the talk moves to the next node.
+    /// Use a name starting with `global.` to set a variable shared by all the talks.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// TalkBuilder::default().say("Nice to meet you!").set("met_ferris", true);
+    /// ```
+    pub fn set(mut self, name: impl Into<VariableName>, value: impl Into<TalkValue>) -> Self {
+        let set_node = BuildNode {
+            id: Uuid::new_v4().to_string(),
+            components: vec![Box::new(SetNode {
+                name: name.into(),
+                value: value.into(),
+            })],
+            ..default()
+        };
+        self.queue.push_back(set_node);
+        self
+    }
+
+    /// Add a node that increments a numeric variable when reached. It will spawn an entity with `IncrementNode`.
+    ///
+    /// The node is traversed automatically like the one from [`TalkBuilder::set`].
+    /// Pass a negative amount to decrement the variable.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// TalkBuilder::default().say("Thanks, friend!").increment("ferris_affinity", 1);
+    /// ```
+    pub fn increment(
+        mut self,
+        name: impl Into<VariableName>,
+        amount: impl Into<TalkValue>,
+    ) -> Self {
+        let increment_node = BuildNode {
+            id: Uuid::new_v4().to_string(),
+            components: vec![Box::new(IncrementNode {
+                name: name.into(),
+                amount: amount.into(),
+            })],
+            ..default()
+        };
+        self.queue.push_back(increment_node);
+        self
+    }
+
     /// Create a relationship manually from the latest node to the node identified by the given id.
     ///
     /// # Note
@@ -253,6 +419,48 @@ impl TalkBuilder {
         }
     }
 
+    /// Set the id of the latest node added to the builder, replacing the generated one.
+    /// The id is added to the node entity with the [`NodeId`] component.
+    ///
+    /// Give stable ids to the nodes to save and restore the progress of a talk with [`crate::state::TalkState`].
+    /// Set it right after adding the node, before using `last_node_id` or `connect_to` with it.
+    /// The ids must be unique in the talk, entry points included, or the talk is not built.
+    ///
+    /// # Panics
+    /// If you call this method on an empty builder it will panic.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// let builder = TalkBuilder::default().say("hello").with_id("greeting");
+    ///
+    /// assert_eq!(builder.last_node_id(), "greeting");
+    /// ```
+    pub fn with_id(mut self, id: impl Into<BuildNodeId>) -> Self {
+        match self.queue.back_mut() {
+            None => panic!("You can't set the id of the last node of an empty builder"),
+            Some(node) => node.id = id.into(),
+        };
+        self
+    }
+
+    /// Make the latest node added to the builder move to the next node by itself after the delay, in seconds.
+    /// It adds the [`AutoAdvance`] component to the node. Choice nodes and end nodes never advance automatically.
+    ///
+    /// # Panics
+    /// If you call this method on an empty builder it will panic.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// let builder = TalkBuilder::default().say("Watch out!").auto_advance(1.5).say("Too late.");
+    /// ```
+    pub fn auto_advance(self, seconds: f32) -> Self {
+        self.with_component(AutoAdvance(seconds))
+    }
+
     /// Add an actor to the builder to be spawned (if not already present in the world, checked with the slug identifier).
     /// # Note
     /// Adding actors to nested builders (when branching) has no effect. Add them to the root builder instead.
@@ -269,6 +477,37 @@ impl TalkBuilder {
         self
     }
 
+    /// Add a custom component to the entity of the actor identified by the slug.
+    ///
+    /// The component is inserted when the talk is built, also if the actor entity was already in the world.
+    ///
+    /// # Note
+    /// Remember to register the type, like the node components. Adding actor components to nested builders has no effect.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::*;
+    /// use bevy::prelude::*;
+    ///
+    /// #[derive(Component, Reflect, Default)]
+    /// #[reflect(Component)]
+    /// struct Mood(String);
+    ///
+    /// let builder = TalkBuilder::default()
+    ///     .add_actor(Actor::new("bob", "Bob").with_portrait("images/bob.png"))
+    ///     .with_actor_component("bob", Mood("grumpy".to_string()))
+    ///     .actor_say("bob", "Hello");
+    /// ```
+    pub fn with_actor_component<C: Component + Reflect>(
+        mut self,
+        actor_slug: impl Into<ActorSlug>,
+        comp: C,
+    ) -> Self {
+        self.actor_components
+            .push((actor_slug.into(), Box::new(comp)));
+        self
+    }
+
     /// Add a talk node with an actor. It will spawn an entity with `TalkText` connected with the actor entity identified by the slug.
     pub fn actor_say(mut self, actor_slug: impl Into<String>, text: impl Into<String>) -> Self {
         let talk_node = BuildNode {
@@ -317,6 +556,42 @@ impl TalkBuilder {
         self
     }
 
+    /// Add a named entry point to the talk, with its own start node followed by the nodes of the given builder.
+    ///
+    /// The builder can be empty and just `connect_to` a node of the talk. Spawn the talk with
+    /// [`TalkCommandsExt::spawn_talk_at`](crate::prelude::TalkCommandsExt::spawn_talk_at) to start it from the entry point.
+    /// The actors must be added to the main builder and the entry points of the inner builders (in the choices) are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_talks::prelude::TalkBuilder;
+    ///
+    /// let builder = TalkBuilder::default()
+    ///     .say("Nice to meet you!")
+    ///     .say("What can I do for you?")
+    ///     .with_id("hub")
+    ///     .entry_point("after_quest", TalkBuilder::default().say("Thanks for the help!").connect_to("hub".to_string()))
+    ///     .entry_point("again", TalkBuilder::default().connect_to("hub".to_string()));
+    /// ```
+    pub fn entry_point(mut self, name: impl Into<String>, builder: TalkBuilder) -> Self {
+        self.entry_points.push((name.into(), builder));
+        self
+    }
+
+    /// Keeps only the components of the nodes (the inner ones and the entry points too) that satisfy the predicate.
+    pub(crate) fn retain_components(&mut self, keep: &impl Fn(&dyn Reflect) -> bool) {
+        self.actor_components.retain(|(_, c)| keep(&**c));
+        for node in self.queue.iter_mut() {
+            node.components.retain(|c| keep(&**c));
+            for (_, _, inner) in node.choices.iter_mut() {
+                inner.retain_components(keep);
+            }
+        }
+        for (_, entry) in self.entry_points.iter_mut() {
+            entry.retain_components(keep);
+        }
+    }
+
     /// Add a component to the latest added node.
     /// If you add a `NodeEventEmitter` component the node will automatically emit the relative event when reached.
     ///
@@ -326,10 +601,18 @@ impl TalkBuilder {
     ///
     /// # Panics
     /// If you call this method on an empty builder it will panic.
-    pub fn with_component<C: Component + Reflect>(mut self, comp: C) -> Self {
+    pub fn with_component<C: Component + Reflect>(self, comp: C) -> Self {
+        self.with_reflect_component(Box::new(comp))
+    }
+
+    /// Add a reflected component (it can be a dynamic value, like the ones from the talk files) to the latest added node.
+    ///
+    /// # Panics
+    /// If you call this method on an empty builder it will panic.
+    pub(crate) fn with_reflect_component(mut self, comp: Box<dyn Reflect>) -> Self {
         match self.queue.back_mut() {
             None => panic!("You can't add a custom component to an empty builder"),
-            Some(node) => node.components.push(Box::new(comp)),
+            Some(node) => node.components.push(comp),
         };
         self
     }
@@ -373,6 +656,35 @@ mod tests {
         assert_eq!(added_node.choices.len(), 1);
     }
 
+    #[rstest]
+    fn choose_if_adds_conditions(talk_builder: TalkBuilder) {
+        let added_node = talk_builder
+            .choose_if(vec![
+                ("A", Some("cond"), TalkBuilder::default().say("a")),
+                ("B", None, TalkBuilder::default().say("b")),
+            ])
+            .queue
+            .pop_front()
+            .unwrap();
+        assert!(!added_node.branching);
+        assert_eq!(added_node.choices[0].1, Some("cond".to_string()));
+        assert_eq!(added_node.choices[1].1, None);
+    }
+
+    #[rstest]
+    fn branch_adds_a_branching_node(talk_builder: TalkBuilder) {
+        let added_node = talk_builder
+            .branch(vec![
+                (Some("cond"), TalkBuilder::default().say("a")),
+                (None, TalkBuilder::default().say("b")),
+            ])
+            .queue
+            .pop_front()
+            .unwrap();
+        assert!(added_node.branching);
+        assert_eq!(added_node.choices.len(), 2);
+    }
+
     #[rstest]
     fn connect_to_adds_entry_to_last_node(talk_builder: TalkBuilder) {
         let mut builder = talk_builder.say("hello");
@@ -423,10 +735,7 @@ mod tests {
 
     #[rstest]
     fn test_add_actor(talk_builder: TalkBuilder) {
-        let actor = Actor {
-            slug: "slug".to_string(),
-            name: "Actor".to_string(),
-        };
+        let actor = Actor::new("slug", "Actor");
         let builder = talk_builder.add_actor(actor.clone());
         assert_eq!(builder.actors.len(), 1);
         assert_eq!(builder.actors[0], actor);
@@ -434,15 +743,33 @@ mod tests {
 
     #[rstest]
     fn test_actor_say_success(talk_builder: TalkBuilder) {
-        let builder = talk_builder.add_actor(Actor {
-            slug: "slug".to_string(),
-            name: "Actor".to_string(),
-        });
+        let builder = talk_builder.add_actor(Actor::new("slug", "Actor"));
         let builder = builder.actor_say("slug", "hello");
         assert_eq!(builder.queue.len(), 1);
         assert_eq!(builder.queue[0].actors[0], "slug");
     }
 
+    #[rstest]
+    fn set_and_increment_add_nodes(talk_builder: TalkBuilder) {
+        let builder = talk_builder.set("flag", true).increment("count", 2);
+        assert_eq!(builder.queue.len(), 2);
+        assert!(builder.queue[0].components[0].is::<SetNode>());
+        assert!(builder.queue[1].components[0].is::<IncrementNode>());
+    }
+
+    #[rstest]
+    fn with_id_sets_the_last_node_id(talk_builder: TalkBuilder) {
+        let builder = talk_builder.say("hello").say("world").with_id("world");
+        assert_ne!(builder.queue[0].id, "world");
+        assert_eq!(builder.queue[1].id, "world");
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn with_id_on_empty_panics(talk_builder: TalkBuilder) {
+        talk_builder.with_id("id");
+    }
+
     #[derive(Component, Reflect)]
     struct MyComp;
 
@@ -0,0 +1,191 @@
+//! Spawn talks directly from the `TalkData` assets, built as soon as the assets are loaded.
+
+use bevy::{asset::LoadState, ecs::system::Command, prelude::*};
+
+use crate::prelude::{
+    BuildTalkCommand, Talk, TalkBuilder, TalkData, TalkLoadFailedEvent, TalkReadyEvent,
+};
+
+/// A bundle to spawn a talk from a `TalkData` asset, without waiting for it to load.
+///
+/// The dialogue graph is built when the asset is available, then a [`TalkReadyEvent`] is sent.
+/// If the asset fails to load (or the talk can't be built from it) a [`TalkLoadFailedEvent`] is sent instead.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn spawn(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn(TalkBundle::new(server.load("talks/simple.talk.ron")));
+///     // or start it from an entry point
+///     commands.spawn(TalkBundle::new(server.load("talks/simple.talk.ron")).at("after_quest"));
+/// }
+///
+/// fn ready(mut ready_events: EventReader<TalkReadyEvent>, mut next: EventWriter<NextNodeRequest>) {
+///     for ev in ready_events.read() {
+///         next.send(NextNodeRequest::new(ev.0));
+///     }
+/// }
+/// ```
+#[derive(Bundle, Default)]
+pub struct TalkBundle {
+    /// The talk, built when the asset is loaded.
+    pub talk: Talk,
+    /// The handle of the talk asset.
+    pub data: Handle<TalkData>,
+    /// Marks the talk to build.
+    pending: PendingTalkBuild,
+}
+
+impl TalkBundle {
+    /// Creates a bundle for the talk asset.
+    pub fn new(data: Handle<TalkData>) -> Self {
+        Self { data, ..default() }
+    }
+
+    /// Start the talk at the given entry point instead of the default start node.
+    pub fn at(mut self, entry_point: impl Into<String>) -> Self {
+        self.pending.entry_point = Some(entry_point.into());
+        self
+    }
+}
+
+/// Marker component for the talks spawned with a [`TalkBundle`] that are not built yet.
+#[derive(Component, Default, Debug, Clone)]
+pub(crate) struct PendingTalkBuild {
+    /// The entry point to start the talk at.
+    entry_point: Option<String>,
+}
+
+/// Builds the talks spawned with a `TalkBundle` when their asset is loaded,
+/// or sends a `TalkLoadFailedEvent` if it failed to load.
+pub(crate) fn build_pending_talks(
+    mut cmd: Commands,
+    talks: Query<(Entity, &Handle<TalkData>, &PendingTalkBuild)>,
+    assets: Res<Assets<TalkData>>,
+    server: Res<AssetServer>,
+    mut failed_events: EventWriter<TalkLoadFailedEvent>,
+) {
+    for (talk, handle, pending) in &talks {
+        if let Some(data) = assets.get(handle) {
+            let mut build = BuildTalkCommand::new(talk, data.fill_builder(TalkBuilder::default()));
+            if let Some(entry) = &pending.entry_point {
+                build = build.at(entry.clone());
+            }
+            cmd.entity(talk).remove::<PendingTalkBuild>();
+            cmd.add(BuildPendingTalkCommand {
+                build,
+                handle: handle.clone(),
+            });
+        } else if server.get_load_state(handle) == Some(LoadState::Failed) {
+            cmd.entity(talk).remove::<PendingTalkBuild>();
+            failed_events.send(TalkLoadFailedEvent {
+                talk,
+                handle: handle.clone(),
+            });
+        }
+    }
+}
+
+/// The command that builds a talk from its loaded asset and sends the event with the outcome.
+struct BuildPendingTalkCommand {
+    /// The command to build the talk.
+    build: BuildTalkCommand,
+    /// The handle of the talk asset.
+    handle: Handle<TalkData>,
+}
+
+impl Command for BuildPendingTalkCommand {
+    fn apply(self, world: &mut World) {
+        let talk = self.build.parent;
+        self.build.apply(world);
+        // the build errors are logged and leave the talk without nodes
+        match world.get::<Talk>(talk).and_then(|t| t.current_node) {
+            Some(_) => world.send_event(TalkReadyEvent(talk)),
+            None => world.send_event(TalkLoadFailedEvent {
+                talk,
+                handle: self.handle,
+            }),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::TypeRegistry;
+
+    use crate::{
+        prelude::*,
+        ron_loader::loader::parse_ron,
+        tests::{single, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// Adds a talk asset with an entry point.
+    fn add_talk(app: &mut App) -> Handle<TalkData> {
+        let ron = r#"(actors: [], script: [(id: 1, text: Some("Hello")), (id: 2, text: Some("Welcome back"))], entry_points: {"again": 2})"#;
+        let data = parse_ron(ron.as_bytes(), &TypeRegistry::default()).unwrap();
+        app.world.resource_mut::<Assets<TalkData>>().add(data)
+    }
+
+    /// The talks of the events of the given type.
+    fn event_talks<E: Event>(app: &App, talk_of: impl Fn(&E) -> Entity) -> Vec<Entity> {
+        let events = app.world.resource::<Events<E>>();
+        events.get_reader().read(events).map(talk_of).collect()
+    }
+
+    #[test]
+    fn talk_is_built_when_the_asset_is_loaded() {
+        let mut app = talks_minimal_app();
+        let handle = add_talk(&mut app);
+        let talk = app.world.spawn(TalkBundle::new(handle)).id();
+        app.update();
+
+        assert!(app.world.get::<PendingTalkBuild>(talk).is_none());
+        assert_eq!(event_talks(&app, |e: &TalkReadyEvent| e.0), vec![talk]);
+        assert!(event_talks(&app, |e: &TalkLoadFailedEvent| e.talk).is_empty());
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "Hello");
+    }
+
+    #[test]
+    fn talk_is_built_at_the_entry_point() {
+        let mut app = talks_minimal_app();
+        let handle = add_talk(&mut app);
+        app.world.spawn(TalkBundle::new(handle).at("again"));
+        app.update();
+
+        let (entry, _) = single::<(&EntryPoint, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(entry.0, "again");
+    }
+
+    #[test]
+    fn talk_waits_for_the_asset() {
+        let mut app = talks_minimal_app();
+        let talk = app.world.spawn(TalkBundle::default()).id();
+        app.update();
+
+        assert!(app.world.get::<PendingTalkBuild>(talk).is_some());
+        assert!(app.world.get::<Children>(talk).is_none());
+        assert!(event_talks(&app, |e: &TalkReadyEvent| e.0).is_empty());
+    }
+
+    #[test]
+    fn failed_build_is_reported() {
+        let mut app = talks_minimal_app();
+        let handle = add_talk(&mut app);
+        let talk = app.world.spawn(TalkBundle::new(handle).at("missing")).id();
+        app.update();
+
+        assert!(event_talks(&app, |e: &TalkReadyEvent| e.0).is_empty());
+        assert_eq!(
+            event_talks(&app, |e: &TalkLoadFailedEvent| e.talk),
+            vec![talk]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Conditions to gate choices and branches of the dialogue graphs.
+
+use bevy::{
+    ecs::system::ReadOnlySystem,
+    prelude::*,
+    utils::{hashbrown::hash_map::Entry, HashMap},
+};
+
+use crate::variables::Variables;
+
+/// The identifier of a named condition.
+///
+/// Conditions are registered in the app with [`TalkConditionsAppExt::register_talk_condition`]
+/// and referenced by their id in the builder (`choose_if`, `branch`) and in the talk.ron files.
+/// If no condition is registered with an id, the variable with the same name is used (see [`crate::variables`]).
+pub type ConditionId = String;
+
+/// Resource holding the registered conditions and the result of their latest evaluation.
+///
+/// The conditions are evaluated once per frame, right before the requests are handled.
+#[derive(Resource, Default)]
+pub struct TalkConditions {
+    /// The registered condition systems.
+    systems: HashMap<ConditionId, Box<dyn ReadOnlySystem<In = (), Out = bool>>>,
+    /// The results of the latest evaluation of the conditions.
+    results: HashMap<ConditionId, bool>,
+}
+
+impl TalkConditions {
+    /// Checks if the condition with the given id holds. Unknown conditions never hold.
+    ///
+    /// The unknown conditions of a talk are reported with a warning when the talk is built.
+    pub fn holds(&self, id: &str) -> bool {
+        match self.results.get(id) {
+            Some(result) => *result,
+            None => {
+                debug!("Condition {id} is not registered. Treating it as false.");
+                false
+            }
+        }
+    }
+
+    /// Checks if a condition is registered with the given id.
+    pub(crate) fn is_registered(&self, id: &str) -> bool {
+        self.systems.contains_key(id)
+    }
+
+    /// Checks if the optional condition holds for the given talk. A missing condition always holds.
+    ///
+    /// If no condition is registered with the id, the variable with the same name is checked instead:
+    /// the condition holds if the variable is truthy. Unset variables are false.
+    pub(crate) fn holds_for(
+        &self,
+        id: &Option<ConditionId>,
+        talk: Entity,
+        variables: &Variables,
+    ) -> bool {
+        let Some(id) = id else {
+            return true;
+        };
+        if let Some(result) = self.results.get(id) {
+            return *result;
+        }
+        match variables.get(talk, id) {
+            Some(value) => value.is_truthy(),
+            None => {
+                debug!(
+                    "Condition {id} is not registered nor a set variable. Treating it as false."
+                );
+                false
+            }
+        }
+    }
+}
+
+/// Extension trait for [`App`] to register conditions for the dialogue graphs.
+pub trait TalkConditionsAppExt {
+    /// Registers a named condition. The condition can be any read-only system returning a `bool`,
+    /// the same kind of systems used as run conditions.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_talks::prelude::*;
+    ///
+    /// #[derive(Resource, Default)]
+    /// struct Inventory {
+    ///     keys: u32,
+    /// }
+    ///
+    /// App::new()
+    ///     .init_resource::<Inventory>()
+    ///     .register_talk_condition("has_key", |inv: Res<Inventory>| inv.keys > 0);
+    /// ```
+    fn register_talk_condition<M>(
+        &mut self,
+        id: impl Into<ConditionId>,
+        condition: impl Condition<M>,
+    ) -> &mut Self;
+}
+
+impl TalkConditionsAppExt for App {
+    fn register_talk_condition<M>(
+        &mut self,
+        id: impl Into<ConditionId>,
+        condition: impl Condition<M>,
+    ) -> &mut Self {
+        let mut system = IntoSystem::into_system(condition);
+        system.initialize(&mut self.world);
+
+        let mut conditions = self
+            .world
+            .get_resource_or_insert_with(TalkConditions::default);
+        match conditions.systems.entry(id.into()) {
+            Entry::Occupied(e) => warn!("Condition {} already registered. Skipping.", e.key()),
+            Entry::Vacant(e) => {
+                e.insert(Box::new(system));
+            }
+        }
+        self
+    }
+}
+
+/// Exclusive system that evaluates all the registered conditions and stores the results.
+pub(crate) fn evaluate_conditions(world: &mut World) {
+    world.resource_scope(|world, mut conditions: Mut<TalkConditions>| {
+        let TalkConditions { systems, results } = &mut *conditions;
+        for (id, system) in systems.iter_mut() {
+            results.insert(id.clone(), system.run_readonly((), world));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::talks_minimal_app;
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Flag(bool);
+
+    #[test]
+    fn registered_conditions_are_evaluated() {
+        let mut app = talks_minimal_app();
+        app.init_resource::<Flag>()
+            .register_talk_condition("flag", |f: Res<Flag>| f.0);
+        app.update();
+        assert!(!app.world.resource::<TalkConditions>().holds("flag"));
+
+        app.world.resource_mut::<Flag>().0 = true;
+        app.update();
+        assert!(app.world.resource::<TalkConditions>().holds("flag"));
+    }
+
+    #[test]
+    fn unknown_condition_does_not_hold() {
+        let conditions = TalkConditions::default();
+        assert!(!conditions.holds("unknown"));
+    }
+}
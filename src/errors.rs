@@ -1,24 +1,55 @@
 //! Errors that can happen when using `bevy_talks`.
 
+use bevy::prelude::Entity;
 use thiserror::Error;
 
-use crate::prelude::ActorSlug;
+use crate::prelude::{ActorSlug, BuildNodeId, ConditionId, JumpTarget};
 
-/// Errors when moving to the next action
-#[derive(Error, Debug, PartialEq, Eq)]
+/// Errors when handling the requests to move through a talk.
+///
+/// The errors carry the entities of the nodes involved. They are sent with the talk and the kind of request
+/// in a [`crate::prelude::TalkErrorEvent`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum NextActionError {
-    /// NextActionRequest error.
-    #[error("No next action found.")]
-    NoNextAction,
-    /// NextActionRequest error.
-    #[error("Current node is a Choice. Cannot just advance.")]
-    ChoicesNotHandled,
-    /// ChooseActionRequest error.
-    #[error("The current node has no choice with the given entity. (If the talk has not started you need to Next first)")]
-    BadChoice,
-    /// Requests error.
-    #[error("No talk was found with the given entity from the event.")]
-    NoTalk,
+    /// `NextNodeRequest` error. The node has no following node.
+    #[error("No next action found after node {0:?}.")]
+    NoNextAction(Entity),
+    /// `NextNodeRequest` error. The node has more than one following node.
+    #[error("Node {0:?} is a Choice. Cannot just advance.")]
+    ChoicesNotHandled(Entity),
+    /// `ChooseNodeRequest` error. The chosen entity does not follow the current node.
+    #[error("Node {node:?} has no choice with entity {chosen:?}. (If the talk has not started you need to Next first)")]
+    BadChoice {
+        /// The current node.
+        node: Entity,
+        /// The chosen entity.
+        chosen: Entity,
+    },
+    /// `ChooseNodeRequest` error. The condition of the chosen choice does not hold.
+    #[error("The choice of node {node:?} leading to {chosen:?} is not available, its condition does not hold.")]
+    UnavailableChoice {
+        /// The current node.
+        node: Entity,
+        /// The chosen entity.
+        chosen: Entity,
+    },
+    /// Requests error. No branch of the branch node can be followed.
+    #[error("No branch of the branch node {0:?} has a condition that holds.")]
+    NoAvailableBranch(Entity),
+    /// Requests error. The automatic nodes form a loop, the entity is the first repeated node.
+    #[error(
+        "The talk would loop forever through branch, set and increment nodes (from node {0:?})."
+    )]
+    EndlessLoop(Entity),
+    /// Requests error. The entity from the request is not a built talk.
+    #[error("No talk was found with entity {0:?} from the request.")]
+    NoTalk(Entity),
+    /// `JumpToNodeRequest` error. The target node is not in the talk.
+    #[error("The node to jump to ({0:?}) is not in the talk.")]
+    NodeNotFound(JumpTarget),
+    /// `PreviousNodeRequest` error. The history is empty or locked before the current node.
+    #[error("Cannot go back from node {0:?}, there is no previous node or the history is locked.")]
+    NoPreviousNode(Entity),
 }
 
 /// Errors from the builder
@@ -27,4 +58,38 @@ pub enum BuildError {
     /// An action has a non-existent actor
     #[error("Tried to use non-existent actor {0} in the builder. Did you forget to add it?")]
     InvalidActor(ActorSlug),
+    /// A node has a component whose type is not registered as a component
+    #[error("The component {0} is not registered. Did you forget to register it with #[reflect(Component)]?")]
+    UnregisteredComponent(String),
+    /// The entry point to spawn the talk at is not in the builder
+    #[error("The talk has no entry point named {0}.")]
+    UnknownEntryPoint(String),
+    /// Two nodes of the builder have the same id
+    #[error("Multiple nodes have the same id {0} in the builder.")]
+    DuplicateNodeId(BuildNodeId),
+    /// A choice or branch condition is not registered nor a variable set when the talk is built or by the talk itself.
+    /// It stops the build only if `deny_unknown_conditions` is set in the `TalksSettings`,
+    /// otherwise it is logged as a warning: the condition doesn't hold until the variable is set.
+    #[error("The condition {0} is not registered nor a set variable. It won't hold until the variable is set.")]
+    UnknownCondition(ConditionId),
+}
+
+/// Errors when saving or restoring the state of a talk
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TalkStateError {
+    /// The entity has no `Talk` component.
+    #[error("Entity {0:?} is not a talk.")]
+    NoTalk(Entity),
+    /// The talk has no current node. It happens if the talk graph is not built yet.
+    #[error("The talk has no current node. Is it built?")]
+    NoCurrentNode,
+    /// The current node has no `NodeId`, so it cannot be saved.
+    #[error("The current node has no NodeId. Only nodes spawned by the builder can be saved.")]
+    UnidentifiedNode,
+    /// The saved node is not in the talk. The talk was probably built from different data.
+    #[error("No node with id {0} in the talk. Was it built from different data?")]
+    UnknownNode(BuildNodeId),
+    /// The saved entry point is not in the talk. The talk was probably built from different data.
+    #[error("No entry point named {0} in the talk. Was it built from different data?")]
+    UnknownEntryPoint(String),
 }
@@ -19,8 +19,16 @@ impl Plugin for TalksEventsPlugin {
         app.add_event::<NextNodeRequest>()
             .add_event::<ChooseNodeRequest>()
             .add_event::<RefireNodeRequest>()
+            .add_event::<JumpToNodeRequest>()
+            .add_event::<PreviousNodeRequest>()
+            .add_event::<ResetTalkRequest>()
             .add_event::<StartEvent>()
             .add_event::<EndEvent>()
+            .add_event::<ResetEvent>()
+            .add_event::<ChoiceTimedOutEvent>()
+            .add_event::<TalkReadyEvent>()
+            .add_event::<TalkLoadFailedEvent>()
+            .add_event::<TalkErrorEvent>()
             .register_node_event::<TextNode, TextNodeEvent>()
             .register_node_event::<ChoiceNode, ChoiceNodeEvent>()
             .register_node_event::<JoinNode, JoinNodeEvent>()
@@ -1,7 +1,10 @@
 //! Events the plugin emits.
 use bevy::prelude::*;
 
-use crate::prelude::{Actor, Choice, ChoiceNode, JoinNode, LeaveNode, TextNode};
+use crate::prelude::{
+    Actor, Choice, ChoiceNode, JoinNode, LeaveNode, NextActionError, RequestKind, TalkData,
+    TextNode,
+};
 
 use super::{NodeEventEmitter, ReflectEvent};
 
@@ -16,21 +19,75 @@ pub struct StartEvent(pub Entity);
 #[derive(Event)]
 pub struct EndEvent(pub Entity);
 
+/// Event sent when the time of a choice node with a `ChoiceTimeout` runs out,
+/// right before its default choice is chosen with a `ChooseNodeRequest`.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ChoiceTimedOutEvent {
+    /// The talk parent entity.
+    pub talk: Entity,
+    /// The choice node.
+    pub node: Entity,
+    /// The node of the default choice.
+    pub next: Entity,
+}
+
+/// Reset event sent when a talk is moved back to its start node by a `ResetTalkRequest`.
+/// Contains the talk parent entity.
+#[derive(Event)]
+pub struct ResetEvent(pub Entity);
+
+/// Ready event sent when a talk spawned with a `TalkBundle` is built from its loaded asset.
+/// Contains the talk parent entity.
+#[derive(Event)]
+pub struct TalkReadyEvent(pub Entity);
+
+/// Event sent when a talk spawned with a `TalkBundle` can't be built,
+/// because its asset failed to load or the talk could not be built from it (the build error is logged).
+#[derive(Event, Debug, Clone)]
+pub struct TalkLoadFailedEvent {
+    /// The talk parent entity.
+    pub talk: Entity,
+    /// The handle of the talk asset.
+    pub handle: Handle<TalkData>,
+}
+
+/// Error event sent when a request to a talk fails, for example a `NextNodeRequest` on a choice node.
+///
+/// The errors are also logged, unless `log_errors` is disabled in the `TalksSettings` resource.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct TalkErrorEvent {
+    /// The talk parent entity from the failed request.
+    pub talk: Entity,
+    /// The kind of the failed request.
+    pub request_kind: RequestKind,
+    /// The error.
+    pub error: NextActionError,
+}
+
 /// Emitted when a text node is reached.
 #[derive(Event, Reflect, Default, Clone)]
 #[reflect(Event)]
 pub struct TextNodeEvent {
-    /// The text from the node.
+    /// The text from the node, with the `{variable}` and `{actor:slug}` placeholders resolved.
     pub text: String,
+    /// The text from the node as written, before the placeholders are resolved.
+    pub raw_text: String,
     /// The actor names from the node.
     pub actors: Vec<String>,
+    /// The actors from the node, with their portraits and voices.
+    pub speakers: Vec<Actor>,
+    /// If the line has been read before (see [`crate::prelude::ReadHistory`]).
+    pub seen: bool,
 }
 
 impl NodeEventEmitter for TextNode {
     fn make(&self, actors: &[Actor]) -> Box<dyn Reflect> {
         Box::from(TextNodeEvent {
             text: self.0.clone(),
+            raw_text: self.0.clone(),
             actors: actors.iter().map(|a| a.name.clone()).collect(),
+            speakers: actors.to_vec(),
+            seen: false,
         })
     }
 }
@@ -2,6 +2,54 @@
 
 use bevy::prelude::*;
 
+use crate::prelude::BuildNodeId;
+
+/// The kinds of requests a talk can receive. It is used to report which request failed in a `TalkErrorEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    /// A `NextNodeRequest`.
+    Next,
+    /// A `ChooseNodeRequest`.
+    Choose,
+    /// A `JumpToNodeRequest`.
+    Jump,
+    /// A `PreviousNodeRequest`.
+    Previous,
+    /// A `ResetTalkRequest`.
+    Reset,
+    /// A `RefireNodeRequest`.
+    Refire,
+}
+
+/// A request sent to a talk.
+pub(crate) trait TalkRequest: Event {
+    /// The kind of the request.
+    const KIND: RequestKind;
+
+    /// The talk the request is for.
+    fn talk(&self) -> Entity;
+}
+
+/// Implements [`TalkRequest`] for the requests with a `talk` field.
+macro_rules! impl_talk_request {
+    ($request:ty, $kind:expr) => {
+        impl TalkRequest for $request {
+            const KIND: RequestKind = $kind;
+
+            fn talk(&self) -> Entity {
+                self.talk
+            }
+        }
+    };
+}
+
+impl_talk_request!(RefireNodeRequest, RequestKind::Refire);
+impl_talk_request!(NextNodeRequest, RequestKind::Next);
+impl_talk_request!(ChooseNodeRequest, RequestKind::Choose);
+impl_talk_request!(JumpToNodeRequest, RequestKind::Jump);
+impl_talk_request!(PreviousNodeRequest, RequestKind::Previous);
+impl_talk_request!(ResetTalkRequest, RequestKind::Reset);
+
 /// Event to request the current node to re-send all its events.
 #[derive(Event)]
 pub struct RefireNodeRequest {
@@ -18,7 +66,7 @@ impl RefireNodeRequest {
 /// Event to request the next node in a `Talk`. It requires an entity with the `Talk` component you want to update.
 ///
 /// This event is typically used wired to an input from the player, e.g. a mouse click to advance the current dialogue.
-/// It can fail (and logs an error) in case there is no next action or in case the current action is a choice action.
+/// It can fail (and sends a `TalkErrorEvent`) in case there is no next action or in case the current action is a choice action.
 #[derive(Event)]
 pub struct NextNodeRequest {
     /// The entity with the `Talk` component you want to update.
@@ -32,6 +80,24 @@ impl NextNodeRequest {
     }
 }
 
+/// Event to go back to the previous node in the history of a `Talk` (see [`crate::prelude::TalkHistory`]),
+/// emitting its events again. It requires an entity with the `Talk` component you want to update.
+///
+/// It fails (and sends a `TalkErrorEvent`) if there is no previous node, or if going back would cross a choice node,
+/// set and increment nodes or a `RewindBarrier` node (see the `TalksSettings` to allow the first two).
+#[derive(Event)]
+pub struct PreviousNodeRequest {
+    /// The entity with the `Talk` component you want to update.
+    pub talk: Entity,
+}
+
+impl PreviousNodeRequest {
+    /// Creates a new `PreviousNodeRequest`.
+    pub fn new(talk: Entity) -> Self {
+        Self { talk }
+    }
+}
+
 /// An event to jump to some specific node in a graph. It requires an entity with the `Talk` component you want to update.
 ///
 /// It is typically used when you want to go to a target node from a choice node.
@@ -51,4 +117,88 @@ impl ChooseNodeRequest {
     }
 }
 
-// TODO: reset talk event request
+/// Event to request to jump to any node of a `Talk`, not only the ones following the current node.
+/// It requires an entity with the `Talk` component you want to update and the node to jump to.
+///
+/// The events of the target node are emitted (and the start and end events, like when moving to the next node).
+/// It is useful for debug tools, to skip cutscenes or to resume from a checkpoint.
+#[derive(Event)]
+pub struct JumpToNodeRequest {
+    /// The entity with the `Talk` component you want to update.
+    pub talk: Entity,
+    /// The node to jump to.
+    pub target: JumpTarget,
+}
+
+impl JumpToNodeRequest {
+    /// Creates a new `JumpToNodeRequest`. The target can be a node entity or a node id.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_talks::prelude::*;
+    ///
+    /// let talk = Entity::PLACEHOLDER;
+    /// // jump to the node with the id set with `with_id` in the builder
+    /// let request = JumpToNodeRequest::new(talk, "checkpoint");
+    /// // or to the node of the action with id 3 in a talk.ron file
+    /// let request = JumpToNodeRequest::new(talk, 3);
+    /// ```
+    pub fn new(talk: Entity, target: impl Into<JumpTarget>) -> Self {
+        Self {
+            talk,
+            target: target.into(),
+        }
+    }
+}
+
+/// The node to jump to with a `JumpToNodeRequest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JumpTarget {
+    /// The node entity.
+    Entity(Entity),
+    /// The node with the given `NodeId`.
+    Id(BuildNodeId),
+}
+
+impl From<Entity> for JumpTarget {
+    fn from(value: Entity) -> Self {
+        JumpTarget::Entity(value)
+    }
+}
+
+impl From<&str> for JumpTarget {
+    fn from(value: &str) -> Self {
+        JumpTarget::Id(value.to_string())
+    }
+}
+
+impl From<String> for JumpTarget {
+    fn from(value: String) -> Self {
+        JumpTarget::Id(value)
+    }
+}
+
+/// The ids of the nodes built from talk.ron files are the action ids.
+impl From<usize> for JumpTarget {
+    fn from(value: usize) -> Self {
+        JumpTarget::Id(value.to_string())
+    }
+}
+
+/// Event to request to restart a `Talk` from its start node. It requires an entity with the `Talk` component you want to reset.
+///
+/// The current node goes back to the start node, `has_started` is set to false and the nodes are no longer visited.
+/// The talk variables are kept. A `ResetEvent` is sent when the talk is reset.
+#[derive(Event)]
+pub struct ResetTalkRequest {
+    /// The entity with the `Talk` component you want to reset.
+    pub talk: Entity,
+}
+
+impl ResetTalkRequest {
+    /// Creates a new `ResetTalkRequest`.
+    pub fn new(talk: Entity) -> Self {
+        Self { talk }
+    }
+}
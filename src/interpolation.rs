@@ -0,0 +1,91 @@
+//! Interpolation of variables and actor names in the texts.
+
+/// The prefix of the keys that refer to actors, for example `{actor:ferris}`.
+pub(crate) const ACTOR_PREFIX: &str = "actor:";
+
+/// Replaces the `{key}` placeholders in the template with the values returned by `resolve`.
+/// Use `{{` and `}}` to write literal braces.
+///
+/// Returns the interpolated text and the keys that could not be resolved.
+/// The placeholders with unknown keys are left as they are in the text.
+pub(crate) fn interpolate(
+    template: &str,
+    resolve: impl Fn(&str) -> Option<String>,
+) -> (String, Vec<String>) {
+    let mut text = String::with_capacity(template.len());
+    let mut unknown = vec![];
+    let mut rest = template;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        text.push_str(&rest[..i]);
+        let brace = &rest[i..i + 1];
+        rest = &rest[i + 1..];
+
+        // escaped braces
+        if rest.starts_with(brace) {
+            text.push_str(brace);
+            rest = &rest[1..];
+            continue;
+        }
+
+        // a lone closing brace or an unclosed placeholder is kept as it is
+        let end = match (brace, rest.find('}')) {
+            ("{", Some(end)) => end,
+            _ => {
+                text.push_str(brace);
+                continue;
+            }
+        };
+
+        let key = rest[..end].trim();
+        match resolve(key) {
+            Some(value) => text.push_str(&value),
+            None => {
+                text.push('{');
+                text.push_str(&rest[..end]);
+                text.push('}');
+                unknown.push(key.to_string());
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    (text, unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn resolve(key: &str) -> Option<String> {
+        match key {
+            "name" => Some("Ferris".to_string()),
+            "gold" => Some("42".to_string()),
+            _ => None,
+        }
+    }
+
+    #[rstest]
+    #[case("Hello", "Hello")]
+    #[case("Hello {name}", "Hello Ferris")]
+    #[case("{name} has {gold} coins", "Ferris has 42 coins")]
+    #[case("{ name }!", "Ferris!")]
+    #[case("{{name}}", "{name}")]
+    #[case("a } b", "a } b")]
+    #[case("unclosed {name", "unclosed {name")]
+    fn interpolate_known_keys(#[case] template: &str, #[case] expected: &str) {
+        let (text, unknown) = interpolate(template, resolve);
+        assert_eq!(text, expected);
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn unknown_keys_are_kept_and_reported() {
+        let (text, unknown) = interpolate("Hi {name}, {missing} and {other}", resolve);
+        assert_eq!(text, "Hi Ferris, {missing} and {other}");
+        assert_eq!(unknown, vec!["missing".to_string(), "other".to_string()]);
+    }
+}
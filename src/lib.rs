@@ -3,19 +3,38 @@
 use aery::{prelude::*, tuple_traits::RelationEntries};
 use bevy::prelude::*;
 
+use bundle::build_pending_talks;
+use conditions::evaluate_conditions;
+use interpolation::{interpolate, ACTOR_PREFIX};
 use prelude::*;
-use ron_loader::loader::TalksLoader;
-use traverse::{choice_handler, next_handler, set_has_started};
+use read_history::ReadTracking;
+use reload::reload_talks;
+use ron_loader::{loader::TalksLoader, yarn::YarnLoader};
+use timers::advance_timers;
+use traverse::{
+    choice_handler, jump_handler, next_handler, previous_handler, reset_handler, set_has_started,
+    AutoTraversal, Traversal,
+};
 
 pub mod actors;
 pub mod builder;
+pub mod bundle;
+pub mod conditions;
 pub mod errors;
 pub mod events;
+mod interpolation;
+pub mod lint;
 pub mod prelude;
+pub mod read_history;
+pub mod reload;
 pub mod ron_loader;
+pub mod state;
 pub mod talk;
 pub mod talk_asset;
+pub mod timers;
+pub mod transcript;
 mod traverse;
+pub mod variables;
 
 /// The plugin that provides the basics to build and handle dialogues in games.
 ///
@@ -31,19 +50,53 @@ impl Plugin for TalksPlugin {
         }
 
         app.add_plugins(TalksEventsPlugin)
-            .register_asset_loader(TalksLoader)
+            .init_asset_loader::<TalksLoader>()
+            .register_asset_loader(YarnLoader)
             .init_asset::<TalkData>()
+            .init_resource::<TalksSettings>()
+            .init_resource::<TalkConditions>()
+            .init_resource::<GlobalTalkVariables>()
+            .init_resource::<ReadHistory>()
+            // the timers don't advance without the `TimePlugin`, but the talks still work
+            .init_resource::<Time>()
+            .register_type::<NodeId>()
+            .register_type::<BranchNode>()
+            .register_type::<SetNode>()
+            .register_type::<IncrementNode>()
+            .register_type::<EntryPoint>()
+            .register_type::<TalkHistory>()
+            .register_type::<RewindBarrier>()
+            .register_type::<AutoAdvance>()
+            .register_type::<ChoiceTimeout>()
             .configure_sets(PreUpdate, TalksSet)
+            .add_systems(
+                PreUpdate,
+                (reload_talks, build_pending_talks, advance_timers).before(TalksSet),
+            )
             .add_systems(
                 PreUpdate,
                 (
-                    next_handler.pipe(error_logger),
-                    choice_handler.pipe(error_logger),
-                    refire_handler.pipe(error_logger),
-                    set_has_started.after(next_handler),
+                    evaluate_conditions,
+                    // chained so the moves of each kind of request are applied before the next kind
+                    next_handler.pipe(send_error_events),
+                    choice_handler.pipe(send_error_events),
+                    jump_handler.pipe(send_error_events),
+                    previous_handler.pipe(send_error_events),
+                    // before the reset, so a talk started and reset in the same frame ends up not started
+                    set_has_started,
+                    reset_handler.pipe(send_error_events),
+                    refire_handler.pipe(send_error_events),
                 )
+                    .chain()
                     .in_set(TalksSet),
             );
+
+        #[cfg(feature = "ink")]
+        app.register_asset_loader(ron_loader::ink::InkLoader);
+        #[cfg(feature = "json")]
+        app.init_asset_loader::<ron_loader::json::JsonTalksLoader>();
+        #[cfg(feature = "yaml")]
+        app.init_asset_loader::<ron_loader::yaml::YamlTalksLoader>();
     }
 }
 
@@ -51,55 +104,56 @@ impl Plugin for TalksPlugin {
 #[derive(SystemSet, Debug, Default, Clone, PartialEq, Eq, Hash)]
 struct TalksSet;
 
-/// Logs errors from the other systems.
-fn error_logger(In(result): In<Result<(), NextActionError>>) {
-    if let Err(err) = result {
-        error!("Error: {err}");
+/// Settings of the `TalksPlugin`.
+#[derive(Resource, Debug, Clone)]
+pub struct TalksSettings {
+    /// If true (the default), the errors from the requests are logged besides being sent as `TalkErrorEvent`s.
+    pub log_errors: bool,
+    /// If true, a `PreviousNodeRequest` can go back to a choice node to choose again. False by default.
+    pub rewind_across_choices: bool,
+    /// If true, a `PreviousNodeRequest` can go back across set and increment nodes,
+    /// without undoing the changes to the variables. False by default.
+    pub rewind_across_side_effects: bool,
+    /// If true, the talks with conditions that are not registered nor variables are not built.
+    /// False by default: the unknown conditions are only logged as warnings.
+    pub deny_unknown_conditions: bool,
+}
+
+impl Default for TalksSettings {
+    fn default() -> Self {
+        Self {
+            log_errors: true,
+            rewind_across_choices: false,
+            rewind_across_side_effects: false,
+            deny_unknown_conditions: false,
+        }
+    }
+}
+
+/// Sends the errors from the request handlers as `TalkErrorEvent`s, one for each failed request.
+/// They are also logged if enabled in the `TalksSettings`.
+fn send_error_events(
+    In(errors): In<Vec<TalkErrorEvent>>,
+    settings: Res<TalksSettings>,
+    mut error_ev_writer: EventWriter<TalkErrorEvent>,
+) {
+    for event in errors {
+        if settings.log_errors {
+            error!(
+                "{:?} request for talk {:?} failed: {}",
+                event.request_kind, event.talk, event.error
+            );
+        }
+        error_ev_writer.send(event);
     }
 }
 
 /// Handles the `RefireNodeRequest` events. It will emit the events in the current node.
 fn refire_handler(
-    mut cmd: Commands,
     mut reqs: EventReader<RefireNodeRequest>,
-    current_nodes: Query<(Entity, &Parent), With<CurrentNode>>,
-    start: Query<Entity, With<StartNode>>,
-    end: Query<Entity, With<EndNode>>,
-    all_actors: Query<&Actor>,
-    performers: Query<Relations<PerformedBy>>,
-    emitters: Query<&dyn NodeEventEmitter>,
-    type_registry: Res<AppTypeRegistry>,
-    mut start_ev_writer: EventWriter<StartEvent>,
-    mut end_ev_writer: EventWriter<EndEvent>,
-) -> Result<(), NextActionError> {
-    if let Some(event) = reqs.read().next() {
-        for (current_node, talk_parent) in &current_nodes {
-            let this_talk = talk_parent.get();
-            // if this is the talk we want to advance
-            if this_talk == event.talk {
-                // send start event if we are at the start node
-                maybe_emit_start_event(&start, current_node, &mut start_ev_writer, event.talk);
-
-                // send end event if current node is an end node
-                maybe_emit_end_event(&end, current_node, &mut end_ev_writer, event.talk);
-
-                // grab the actors in the next node
-                let actors_in_node = retrieve_actors(&performers, current_node, &all_actors);
-
-                // emit the events in current node
-                emit_events(
-                    &mut cmd,
-                    &emitters,
-                    current_node,
-                    &type_registry,
-                    actors_in_node,
-                );
-                return Ok(());
-            }
-        }
-        return Err(NextActionError::NoTalk);
-    }
-    Ok(())
+    mut traversal: Traversal,
+) -> Vec<TalkErrorEvent> {
+    traversal.handle_all(reqs.read(), |traversal, event| traversal.refire(event.talk))
 }
 
 /// Emits the start event if the current node is a start node.
@@ -145,19 +199,46 @@ pub(crate) fn retrieve_actors(
 }
 
 /// Iterates over the `NodeEventEmitter` in the current node and emits the events.
+/// The choices with a condition that does not hold are removed from the `ChoiceNodeEvent`
+/// and the placeholders in the `TextNodeEvent` are resolved.
+/// The `seen` flags of the text and choices are set from the read history.
+/// The events are recorded in the transcript, if given.
 #[inline]
 pub(crate) fn emit_events(
     cmd: &mut Commands,
     emitters: &Query<&dyn NodeEventEmitter>,
     next_node: Entity,
     type_registry: &Res<AppTypeRegistry>,
+    flow: &AutoTraversal,
+    talk: Entity,
+    all_actors: &Query<&Actor>,
     actors_in_node: Vec<Actor>,
+    read: &ReadTracking,
+    mut transcript: Option<Mut<TalkTranscript>>,
 ) {
     if let Ok(emitters) = emitters.get(next_node) {
         let type_registry = type_registry.read();
 
         for emitter in &emitters {
-            let emitted_event = emitter.make(&actors_in_node);
+            let mut emitted_event = emitter.make(&actors_in_node);
+
+            if let Some(choice_event) = emitted_event.downcast_mut::<ChoiceNodeEvent>() {
+                choice_event
+                    .choices
+                    .retain(|c| flow.holds(talk, &c.condition));
+                for choice in choice_event.choices.iter_mut() {
+                    choice.seen = read.is_choice_seen(talk, next_node, choice.next);
+                }
+            }
+
+            if let Some(text_event) = emitted_event.downcast_mut::<TextNodeEvent>() {
+                text_event.text = resolve_text(&text_event.raw_text, flow, talk, all_actors);
+                text_event.seen = read.is_node_seen(talk, next_node);
+            }
+
+            if let Some(transcript) = transcript.as_mut() {
+                transcript.record(&*emitted_event);
+            }
 
             let event_type_id = emitted_event.type_id();
             // The #[reflect] attribute we put on our event trait generated a new `ReflectEvent` struct
@@ -173,6 +254,28 @@ pub(crate) fn emit_events(
         }
     }
 }
+
+/// Resolves the `{variable}` and `{actor:slug}` placeholders in the text of a talk.
+/// Unknown keys are left in the text and a warning is logged.
+fn resolve_text(
+    raw_text: &str,
+    flow: &AutoTraversal,
+    talk: Entity,
+    all_actors: &Query<&Actor>,
+) -> String {
+    let (text, unknown) = interpolate(raw_text, |key| match key.strip_prefix(ACTOR_PREFIX) {
+        Some(slug) => all_actors
+            .iter()
+            .find(|a| a.slug == slug)
+            .map(|a| a.name.clone()),
+        None => flow.variable(talk, key).map(ToString::to_string),
+    });
+    for key in unknown {
+        warn!("Unknown key {{{key}}} in text \"{raw_text}\" of talk {talk:?}. Neither a variable nor an actor.");
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use bevy::ecs::{
@@ -191,6 +294,13 @@ mod tests {
         app
     }
 
+    /// Spawns a talk entity and builds the given builder in it. Returns the talk entity.
+    pub fn spawn_built_talk(app: &mut App, builder: TalkBuilder) -> Entity {
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder).apply(&mut app.world);
+        talk
+    }
+
     #[inline]
     #[track_caller]
     pub fn get_comp<C: Component>(e: Entity, world: &mut World) -> &C {
@@ -200,12 +310,12 @@ mod tests {
     #[inline]
     #[track_caller]
     pub fn count<Q: WorldQuery>(world: &mut World) -> usize {
-        world.query::<Q>().iter(&world).count()
+        world.query::<Q>().iter(world).count()
     }
 
     #[inline]
     #[track_caller]
-    pub fn single<Q: WorldQuery>(world: &mut World) -> ROQueryItem<Q> {
+    pub fn single<Q: WorldQuery>(world: &mut World) -> ROQueryItem<'_, Q> {
         world.query::<Q>().single(world)
     }
 
@@ -215,8 +325,7 @@ mod tests {
     pub fn setup_and_next(talk_data: &TalkData) -> App {
         let mut app = talks_minimal_app();
         let builder = TalkBuilder::default().fill_with_talk_data(talk_data);
-        BuildTalkCommand::new(app.world.spawn(Talk::default()).id(), builder).apply(&mut app.world);
-        let (talk_ent, _) = single::<(Entity, With<Talk>)>(&mut app.world);
+        let talk_ent = spawn_built_talk(&mut app, builder);
         let (edges, _) = single::<(Relations<FollowedBy>, With<CurrentNode>)>(&mut app.world);
 
         assert_eq!(edges.targets(FollowedBy).len(), 1);
@@ -0,0 +1,338 @@
+//! The lint pass of the talks, that finds the problems the loaders don't reject:
+//! unreachable actions, loops without exit, choice nodes without choices and unused actors.
+
+use std::{collections::VecDeque, fmt, path::Path};
+
+use bevy::{
+    log::warn,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    prelude::{ActionId, ActorSlug, NodeKind, TalkData},
+    ron_loader::loader::RonLoaderError,
+};
+
+/// A problem found in a talk by [`TalkData::lint`].
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TalkLint {
+    /// The action can't be reached from the start (the `Start` action or the first one) or the entry points, so it is never built.
+    #[error("the action {0} cannot be reached from the start or the entry points")]
+    UnreachableAction(ActionId),
+    /// The actions loop without ever reaching the end of the talk.
+    #[error("the actions {0:?} loop without reaching the end of the talk")]
+    NoExit(Vec<ActionId>),
+    /// A choice (or branch) action without choices, where the talk gets stuck.
+    #[error("the action {0} has no choices")]
+    EmptyChoices(ActionId),
+    /// A choice of the action (the action id and the index of the choice) leads back to the action itself.
+    #[error("the choice {1} of action {0} points to the action itself")]
+    SelfChoice(ActionId, usize),
+    /// The actor doesn't perform in any action.
+    #[error("the actor {0} does not perform in any action")]
+    UnusedActor(ActorSlug),
+}
+
+/// How bad a [`TalkLint`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    /// The talk works, but probably not as intended.
+    Warning,
+    /// The talk can't be completed.
+    Error,
+}
+
+impl TalkLint {
+    /// The severity of the lint: loops without exit and actions without choices are errors, the rest are warnings.
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            TalkLint::NoExit(_) | TalkLint::EmptyChoices(_) => LintSeverity::Error,
+            TalkLint::UnreachableAction(_)
+            | TalkLint::SelfChoice(..)
+            | TalkLint::UnusedActor(_) => LintSeverity::Warning,
+        }
+    }
+}
+
+/// The report of the lint pass of a talk, with all the problems found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TalkLintReport {
+    /// The problems found, in the order of the script (the unused actors last).
+    pub lints: Vec<TalkLint>,
+}
+
+impl TalkLintReport {
+    /// True if no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.lints.is_empty()
+    }
+
+    /// True if any of the problems is an error.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// The problems with the `Error` severity.
+    pub fn errors(&self) -> impl Iterator<Item = &TalkLint> {
+        self.with_severity(LintSeverity::Error)
+    }
+
+    /// The problems with the `Warning` severity.
+    pub fn warnings(&self) -> impl Iterator<Item = &TalkLint> {
+        self.with_severity(LintSeverity::Warning)
+    }
+
+    /// The problems with the given severity.
+    fn with_severity(&self, severity: LintSeverity) -> impl Iterator<Item = &TalkLint> {
+        self.lints.iter().filter(move |l| l.severity() == severity)
+    }
+}
+
+impl fmt::Display for TalkLintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lints = self.lints.iter().map(|l| l.to_string());
+        write!(f, "{}", lints.collect::<Vec<_>>().join("; "))
+    }
+}
+
+/// When to run the lint pass while loading the talk files (talk.ron, talk.json and talk.yaml).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LintMode {
+    /// The talks are not linted (the default).
+    #[default]
+    Off,
+    /// The problems are logged as warnings.
+    Warn,
+    /// The talks with errors fail to load, the warnings are logged.
+    Deny,
+}
+
+/// The settings of the talk files loaders.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn load(asset_server: Res<AssetServer>) {
+///     let handle: Handle<TalkData> = asset_server
+///         .load_with_settings("talks/simple.talk.ron", |s: &mut TalksLoaderSettings| {
+///             s.lint = LintMode::Deny;
+///         });
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TalksLoaderSettings {
+    /// Whether to lint the loaded talks.
+    pub lint: LintMode,
+}
+
+impl LintMode {
+    /// Lints the loaded talk according to the mode.
+    ///
+    /// # Errors
+    /// If the mode is `Deny` and the talk has lint errors.
+    pub(crate) fn check(self, data: TalkData, path: &Path) -> Result<TalkData, RonLoaderError> {
+        if self == LintMode::Off {
+            return Ok(data);
+        }
+        let report = data.lint();
+        if self == LintMode::Deny && report.has_errors() {
+            return Err(RonLoaderError::LintErrors(report));
+        }
+        for lint in report.lints {
+            warn!("Talk {path:?}: {lint}");
+        }
+        Ok(data)
+    }
+}
+
+impl TalkData {
+    /// Checks the talk for the problems that don't prevent loading it, but make it behave unexpectedly.
+    ///
+    /// The talk starts from the `Start` action (or the first action) and from the entry points.
+    /// The actions that can't be reached from them are not built, and the ones that can't reach
+    /// an action without `next` loop forever.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_talks::prelude::*;
+    ///
+    /// fn lint(talks: Res<Assets<TalkData>>) {
+    ///     for (id, talk) in talks.iter() {
+    ///         let report = talk.lint();
+    ///         if report.has_errors() {
+    ///             error!("Talk {id:?} is broken: {report}");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn lint(&self) -> TalkLintReport {
+        let mut lints = vec![];
+
+        // the nodes that follow each action
+        let nexts = (self.script.iter())
+            .map(|(&id, action)| match action.kind {
+                NodeKind::Choice | NodeKind::Branch => {
+                    (id, action.choices.iter().map(|c| c.next).collect())
+                }
+                _ => (id, action.next.into_iter().collect::<Vec<_>>()),
+            })
+            .collect::<HashMap<_, _>>();
+
+        for (&id, action) in &self.script {
+            if matches!(action.kind, NodeKind::Choice | NodeKind::Branch) {
+                if action.choices.is_empty() {
+                    lints.push(TalkLint::EmptyChoices(id));
+                }
+                let self_choices = action.choices.iter().enumerate();
+                let self_choices = self_choices.filter(|(_, c)| c.next == id);
+                lints.extend(self_choices.map(|(i, _)| TalkLint::SelfChoice(id, i)));
+            }
+        }
+
+        // visit the talk from the start and the entry points
+        let starts = self.start_action().into_iter();
+        let reachable = visit(starts.chain(self.entry_points.values().copied()), |id| {
+            nexts[&id].clone()
+        });
+        let unreachable = self.script.keys().filter(|id| !reachable.contains(*id));
+        lints.extend(unreachable.map(|&id| TalkLint::UnreachableAction(id)));
+
+        // visit the talk backwards from the ends (the actions without choices count as ends, they are reported already)
+        let mut previous = HashMap::<ActionId, Vec<ActionId>>::new();
+        for (&id, next) in &nexts {
+            for &n in next {
+                previous.entry(n).or_default().push(id);
+            }
+        }
+        let ends = nexts.iter().filter(|(_, next)| next.is_empty());
+        let exits = visit(ends.map(|(&id, _)| id), |id| {
+            previous.get(&id).cloned().unwrap_or_default()
+        });
+        let trapped = (self.script.keys())
+            .filter(|id| reachable.contains(*id) && !exits.contains(*id))
+            .copied()
+            .collect::<Vec<_>>();
+        if !trapped.is_empty() {
+            lints.push(TalkLint::NoExit(trapped));
+        }
+
+        let performers = (self.script.values())
+            .flat_map(|a| a.actors.iter())
+            .collect::<HashSet<_>>();
+        let unused = self.actors.iter().filter(|a| !performers.contains(&a.slug));
+        lints.extend(unused.map(|a| TalkLint::UnusedActor(a.slug.clone())));
+
+        TalkLintReport { lints }
+    }
+}
+
+/// Visits the graph breadth first from the given actions, returning all the visited ones.
+fn visit(
+    starts: impl Iterator<Item = ActionId>,
+    mut neighbours: impl FnMut(ActionId) -> Vec<ActionId>,
+) -> HashSet<ActionId> {
+    let mut visited = HashSet::new();
+    let mut queue = starts.collect::<VecDeque<_>>();
+    while let Some(id) = queue.pop_front() {
+        if visited.insert(id) {
+            queue.extend(neighbours(id));
+        }
+    }
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bevy::reflect::TypeRegistry;
+    use rstest::rstest;
+
+    use crate::ron_loader::loader::parse_ron;
+
+    use super::*;
+
+    /// Parses a talk.ron file with the given actors and script.
+    fn talk(actors: &str, script: &str) -> TalkData {
+        let ron = format!("(actors: [{actors}], script: [{script}])");
+        parse_ron(ron.as_bytes(), &TypeRegistry::default()).unwrap()
+    }
+
+    #[test]
+    fn no_lints_in_the_assets() {
+        let registry = TypeRegistry::default();
+        let file = include_str!("../assets/talks/full.talk.ron");
+        let report = parse_ron(file.as_bytes(), &registry).unwrap().lint();
+        assert!(report.is_empty(), "{report}");
+    }
+
+    #[rstest]
+    #[case::unreachable(
+        "(id: 1, next: Some(2)), (id: 2), (id: 3, next: Some(2))",
+        vec![TalkLint::UnreachableAction(3)]
+    )]
+    #[case::loop_without_exit(
+        "(id: 1, next: Some(2)), (id: 2, next: Some(3)), (id: 3, next: Some(2))",
+        vec![TalkLint::NoExit(vec![1, 2, 3])]
+    )]
+    #[case::loop_with_exit(
+        "(id: 1, choices: Some([(text: \"Again\", next: 1), (text: \"Bye\", next: 2)])), (id: 2)",
+        vec![TalkLint::SelfChoice(1, 0)]
+    )]
+    #[case::empty_choices(
+        "(id: 1, next: Some(2)), (id: 2, choices: Some([]))",
+        vec![TalkLint::EmptyChoices(2)]
+    )]
+    #[case::start_action_not_first(
+        "(id: 1, text: Some(\"Hello\")), (id: 2, action: Start, next: Some(1))",
+        vec![]
+    )]
+    #[case::empty_branches(
+        "(id: 1, branches: Some([]))",
+        vec![TalkLint::EmptyChoices(1)]
+    )]
+    fn lints_of_the_script(#[case] script: &str, #[case] expected: Vec<TalkLint>) {
+        assert_eq!(talk("", script).lint().lints, expected);
+    }
+
+    #[test]
+    fn entry_points_are_reachable() {
+        let mut data = talk("", "(id: 1), (id: 2, next: Some(3)), (id: 3)");
+        assert_eq!(
+            data.lint().lints,
+            vec![
+                TalkLint::UnreachableAction(2),
+                TalkLint::UnreachableAction(3)
+            ]
+        );
+        data.entry_points.insert("later".to_string(), 2);
+        assert!(data.lint().is_empty());
+    }
+
+    #[test]
+    fn unused_actors() {
+        let actors = "(slug: \"bob\", name: \"Bob\"), (slug: \"alice\", name: \"Alice\")";
+        let report = talk(actors, "(id: 1, actors: [\"bob\"], text: Some(\"Hi\"))").lint();
+        assert_eq!(
+            report.lints,
+            vec![TalkLint::UnusedActor("alice".to_string())]
+        );
+        assert!(!report.has_errors());
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[rstest]
+    #[case(LintMode::Off, true)]
+    #[case(LintMode::Warn, true)]
+    #[case(LintMode::Deny, false)]
+    fn lint_mode_on_load(#[case] mode: LintMode, #[case] loaded: bool) {
+        let data = talk("", "(id: 1, next: Some(1))");
+        let res = mode.check(data, Path::new("loop.talk.ron"));
+        assert_eq!(res.is_ok(), loaded);
+    }
+}
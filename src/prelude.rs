@@ -1,10 +1,19 @@
 //! Prelude for the `bevy_talks` crate.
-pub use super::TalksPlugin;
+pub use super::{TalksPlugin, TalksSettings};
 
 pub use super::actors::*;
 pub use super::builder::{build_command::*, commands::*, *};
+pub use super::bundle::*;
+pub use super::conditions::*;
 pub use super::errors::*;
 pub use super::events::{node_events::*, requests::*, *};
+pub use super::lint::*;
+pub use super::read_history::*;
+pub use super::reload::*;
+pub use super::state::*;
 pub use super::talk::*;
 pub use super::talk_asset::*;
+pub use super::timers::*;
+pub use super::transcript::*;
+pub use super::variables::*;
 pub use bevy_talks_macros::NodeEventEmitter;
@@ -0,0 +1,244 @@
+//! The read history of the talks, to know which lines and choices have ever been seen (across game sessions).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{BuildNodeId, HotReloadTalk, NodeId, TalkData};
+
+/// The nodes and choices that have ever been seen in the talks, for "skip read text" and to grey out the picked choices.
+///
+/// The nodes are identified by the asset path of the talk plus their [`NodeId`] (the action id for the talk files),
+/// so only the talks with a `Handle<TalkData>` component (like the ones spawned with a
+/// [`crate::prelude::TalkBundle`]) or a [`crate::prelude::HotReloadTalk`] component are tracked. The history is updated when the current node of the talks moves,
+/// and it is reflected in the `seen` field of the `TextNodeEvent` and of the choices in the `ChoiceNodeEvent`.
+///
+/// It is serializable, so it can be saved and inserted back in another game session.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn show_lines(mut texts: EventReader<TextNodeEvent>) {
+///     for text in texts.read() {
+///         match text.seen {
+///             true => println!("(already read) {}", text.text),
+///             false => println!("{}", text.text),
+///         }
+///     }
+/// }
+///
+/// fn load_history(mut commands: Commands) {
+///     // deserialize the history saved in a previous session...
+///     commands.insert_resource(ReadHistory::default());
+/// }
+/// ```
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadHistory {
+    /// The ids of the seen nodes, by talk asset path.
+    nodes: BTreeMap<String, BTreeSet<BuildNodeId>>,
+    /// The picked choices, by talk asset path: the id of the choice node and the id of the node the choice leads to.
+    choices: BTreeMap<String, BTreeSet<(BuildNodeId, BuildNodeId)>>,
+}
+
+impl ReadHistory {
+    /// True if the node of the talk at the given asset path has been seen.
+    pub fn is_node_seen(&self, talk_path: &str, node: &str) -> bool {
+        self.nodes.get(talk_path).is_some_and(|n| n.contains(node))
+    }
+
+    /// True if the choice of the choice node, leading to the `next` node, has been picked in the talk at the given asset path.
+    pub fn is_choice_seen(&self, talk_path: &str, choice_node: &str, next: &str) -> bool {
+        self.choices
+            .get(talk_path)
+            .is_some_and(|c| c.contains(&(choice_node.to_string(), next.to_string())))
+    }
+
+    /// Forgets all the seen nodes and choices.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.choices.clear();
+    }
+}
+
+/// System param to read and update the [`ReadHistory`] with the node entities of the talks.
+#[derive(SystemParam)]
+pub(crate) struct ReadTracking<'w, 's> {
+    /// The read history.
+    history: ResMut<'w, ReadHistory>,
+    /// The handles of the talk assets, to get their paths. The handle of the `HotReloadTalk` is used if there is no other.
+    handles: Query<'w, 's, AnyOf<(&'static Handle<TalkData>, &'static HotReloadTalk)>>,
+    /// The ids of the nodes.
+    node_ids: Query<'w, 's, &'static NodeId>,
+    /// The asset server, with the paths of the assets.
+    server: Res<'w, AssetServer>,
+}
+
+impl<'w, 's> ReadTracking<'w, 's> {
+    /// The asset path of the talk and the id of the node, if the talk is tracked.
+    fn key(&self, talk: Entity, node: Entity) -> Option<(String, &BuildNodeId)> {
+        let handle = match self.handles.get(talk).ok()? {
+            (Some(handle), _) | (None, Some(HotReloadTalk(handle))) => handle,
+            (None, None) => return None,
+        };
+        let path = self.server.get_path(handle.id())?;
+        Some((path.to_string(), &self.node_ids.get(node).ok()?.0))
+    }
+
+    /// True if the node of the talk has been seen.
+    pub(crate) fn is_node_seen(&self, talk: Entity, node: Entity) -> bool {
+        self.key(talk, node)
+            .is_some_and(|(path, id)| self.history.is_node_seen(&path, id))
+    }
+
+    /// True if the choice from the choice node to the next node has been picked.
+    pub(crate) fn is_choice_seen(&self, talk: Entity, choice_node: Entity, next: Entity) -> bool {
+        let (Some((path, id)), Ok(next)) = (self.key(talk, choice_node), self.node_ids.get(next))
+        else {
+            return false;
+        };
+        self.history.is_choice_seen(&path, id, &next.0)
+    }
+
+    /// Marks the node of the talk as seen.
+    pub(crate) fn see_node(&mut self, talk: Entity, node: Entity) {
+        if let Some((path, id)) = self.key(talk, node) {
+            let id = id.clone();
+            self.history.nodes.entry(path).or_default().insert(id);
+        }
+    }
+
+    /// Marks the choice from the choice node to the next node as picked.
+    pub(crate) fn see_choice(&mut self, talk: Entity, choice_node: Entity, next: Entity) {
+        let (Some((path, id)), Ok(next)) = (self.key(talk, choice_node), self.node_ids.get(next))
+        else {
+            return;
+        };
+        let choice = (id.clone(), next.0.clone());
+        self.history.choices.entry(path).or_default().insert(choice);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        reflect::TypeRegistry,
+        tasks::{IoTaskPool, TaskPool},
+    };
+
+    use crate::{
+        prelude::*,
+        ron_loader::loader::parse_ron,
+        tests::{spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    const PATH: &str = "talks/choices.talk.ron";
+
+    const SCRIPT: &str = r#"(actors: [], script: [
+        (id: 1, text: Some("Hello"), next: Some(2)),
+        (id: 2, choices: Some([(text: "A", next: 3), (text: "B", next: 4)])),
+        (id: 3, text: Some("Chose A")),
+        (id: 4, text: Some("Chose B")),
+    ])"#;
+
+    /// Builds the talk with the handle of a talk asset, so it is tracked.
+    fn setup() -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        IoTaskPool::get_or_init(TaskPool::new);
+        let handle: Handle<TalkData> = app.world.resource::<AssetServer>().load(PATH);
+        let data = parse_ron(SCRIPT.as_bytes(), &TypeRegistry::default()).unwrap();
+        let talk = spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+        app.world.entity_mut(talk).insert(handle);
+        (app, talk)
+    }
+
+    /// Sends the request and runs the app.
+    fn send(app: &mut App, request: impl Event) {
+        app.world.send_event(request);
+        app.update();
+    }
+
+    /// The last event of the given type.
+    fn last_event<E: Event + Clone>(app: &App) -> E {
+        let events = app.world.resource::<Events<E>>();
+        events.get_reader().read(events).last().cloned().unwrap()
+    }
+
+    /// Moves the talk to the choice node.
+    fn to_choice(app: &mut App, talk: Entity) -> Vec<Choice> {
+        send(app, NextNodeRequest::new(talk));
+        send(app, NextNodeRequest::new(talk));
+        last_event::<ChoiceNodeEvent>(app).choices
+    }
+
+    #[test]
+    fn lines_are_seen_the_second_time() {
+        let (mut app, talk) = setup();
+        send(&mut app, NextNodeRequest::new(talk));
+        assert!(!last_event::<TextNodeEvent>(&app).seen);
+
+        send(&mut app, ResetTalkRequest::new(talk));
+        send(&mut app, NextNodeRequest::new(talk));
+        assert!(last_event::<TextNodeEvent>(&app).seen);
+
+        let history = app.world.resource::<ReadHistory>();
+        assert!(history.is_node_seen(PATH, "1"));
+        assert!(!history.is_node_seen(PATH, "3"));
+    }
+
+    #[test]
+    fn picked_choices_are_seen() {
+        let (mut app, talk) = setup();
+        let choices = to_choice(&mut app, talk);
+        assert!(choices.iter().all(|c| !c.seen));
+        send(&mut app, ChooseNodeRequest::new(talk, choices[0].next));
+
+        send(&mut app, ResetTalkRequest::new(talk));
+        let choices = to_choice(&mut app, talk);
+        let seen = choices.iter().map(|c| c.seen).collect::<Vec<_>>();
+        assert_eq!(seen, vec![true, false]);
+
+        let history = app.world.resource::<ReadHistory>();
+        assert!(history.is_choice_seen(PATH, "2", "3"));
+        assert!(!history.is_choice_seen(PATH, "2", "4"));
+    }
+
+    #[test]
+    fn talks_without_asset_are_not_tracked() {
+        let (mut app, talk) = setup();
+        app.world.entity_mut(talk).remove::<Handle<TalkData>>();
+        send(&mut app, NextNodeRequest::new(talk));
+
+        assert_eq!(*app.world.resource::<ReadHistory>(), ReadHistory::default());
+    }
+
+    #[test]
+    fn hot_reloaded_talks_are_tracked() {
+        let (mut app, talk) = setup();
+        let handle = app.world.entity_mut(talk).take::<Handle<TalkData>>();
+        app.world
+            .entity_mut(talk)
+            .insert(HotReloadTalk(handle.unwrap()));
+        send(&mut app, NextNodeRequest::new(talk));
+
+        assert!(app.world.resource::<ReadHistory>().is_node_seen(PATH, "1"));
+    }
+
+    #[test]
+    fn history_is_serializable() {
+        let (mut app, talk) = setup();
+        let choices = to_choice(&mut app, talk);
+        send(&mut app, ChooseNodeRequest::new(talk, choices[1].next));
+
+        let history = app.world.resource::<ReadHistory>();
+        let saved = serde_ron::to_string(history).unwrap();
+        let loaded: ReadHistory = serde_ron::from_str(&saved).unwrap();
+        assert_eq!(&loaded, history);
+        assert!(loaded.is_node_seen(PATH, "4"));
+        assert!(loaded.is_choice_seen(PATH, "2", "4"));
+    }
+}
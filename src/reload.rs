@@ -0,0 +1,241 @@
+//! Hot reload of the talks spawned from `TalkData` assets.
+
+use bevy::{ecs::system::Command, prelude::*, utils::HashSet};
+
+use crate::{
+    builder::commands::despawn_talk_nodes,
+    prelude::{
+        BuildTalkCommand, EntryPoint, NodeId, Talk, TalkBuilder, TalkData, TalkState, TalkVariables,
+    },
+};
+
+/// Opt-in component that links a [`Talk`] to the `TalkData` asset it was built from.
+///
+/// When the asset is modified (for example when the talk.ron file changes with the asset hot reloading enabled)
+/// the dialogue graph is built again from the new data. The talk stays in the same node if its [`NodeId`]
+/// is still in the new data (the action id for the talk files), otherwise it goes back to the start node.
+/// The visited nodes that are still there and the variables are kept.
+///
+/// No event is sent after the reload. Send a `RefireNodeRequest` to get the events of the current node with the new data.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// #[derive(Resource)]
+/// struct TalkAsset(Handle<TalkData>);
+///
+/// fn spawn(mut commands: Commands, talks: Res<Assets<TalkData>>, talk_asset: Res<TalkAsset>) {
+///     let talk = talks.get(&talk_asset.0).unwrap();
+///     let builder = TalkBuilder::default().fill_with_talk_data(talk);
+///     commands
+///         .spawn_talk(builder)
+///         .insert(HotReloadTalk(talk_asset.0.clone()));
+/// }
+/// ```
+#[derive(Component, Debug, Clone)]
+pub struct HotReloadTalk(pub Handle<TalkData>);
+
+/// Rebuilds the talks with a `HotReloadTalk` component when their asset is modified.
+pub(crate) fn reload_talks(
+    mut cmd: Commands,
+    mut asset_events: EventReader<AssetEvent<TalkData>>,
+    talks: Query<(Entity, &HotReloadTalk)>,
+    assets: Res<Assets<TalkData>>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(data) = assets.get(*id) else {
+            continue;
+        };
+        for (talk, _) in talks.iter().filter(|(_, h)| h.0.id() == *id) {
+            cmd.add(ReloadTalkCommand {
+                talk,
+                data: data.clone(),
+            });
+        }
+    }
+}
+
+/// The command that replaces the dialogue graph of a talk with the one built from the new data,
+/// keeping the progress of the talk where possible.
+pub(crate) struct ReloadTalkCommand {
+    /// The talk parent entity.
+    pub(crate) talk: Entity,
+    /// The new data of the talk.
+    pub(crate) data: TalkData,
+}
+
+impl Command for ReloadTalkCommand {
+    fn apply(self, world: &mut World) {
+        let Some(talk) = world.get::<Talk>(self.talk) else {
+            return;
+        };
+        // start again from the same entry point, if it is still there
+        let entry = (talk.start_node)
+            .and_then(|start| world.get::<EntryPoint>(start))
+            .map(|e| e.0.clone())
+            .filter(|e| self.data.entry_points.contains_key(e));
+        let mut state = TalkState::capture(world, self.talk).unwrap_or_else(|_| TalkState {
+            variables: world
+                .get::<TalkVariables>(self.talk)
+                .cloned()
+                .unwrap_or_default(),
+            ..default()
+        });
+
+        // remove the old graph, the actors are kept for the new one
+        despawn_talk_nodes(world, self.talk);
+        if let Some(mut talk) = world.get_mut::<Talk>(self.talk) {
+            talk.current_node = None;
+            talk.start_node = None;
+        }
+
+        let mut build =
+            BuildTalkCommand::new(self.talk, self.data.fill_builder(TalkBuilder::default()));
+        if let Some(entry) = entry.clone() {
+            build = build.at(entry);
+        }
+        build.apply(world);
+
+        // keep only the nodes that are still in the talk
+        let ids = (world.get::<Children>(self.talk).into_iter())
+            .flat_map(|c| c.iter())
+            .filter_map(|&n| world.get::<NodeId>(n).map(|id| id.0.clone()))
+            .collect::<HashSet<_>>();
+        state.visited.retain(|id| ids.contains(id));
+        // without the saved node or entry point, go back to the start the talk was spawned at
+        let lost_node = (state.current_node.as_ref()).is_some_and(|id| !ids.contains(id));
+        let lost_entry =
+            (state.entry_point.as_ref()).is_some_and(|e| !self.data.entry_points.contains_key(e));
+        if lost_node || lost_entry {
+            state.current_node = None;
+            state.entry_point = entry;
+            state.has_started = false;
+        }
+        if let Err(err) = state.restore(world, self.talk) {
+            error!(
+                "Cannot keep the progress of the reloaded talk {:?}: {err}",
+                self.talk
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::TypeRegistry;
+
+    use crate::{
+        prelude::*,
+        ron_loader::loader::parse_ron,
+        tests::{count, single, spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// Parses a talk.ron file with the given script.
+    fn talk_data(script: &str) -> TalkData {
+        let ron = format!("(actors: [], script: [{script}])");
+        parse_ron(ron.as_bytes(), &TypeRegistry::default()).unwrap()
+    }
+
+    /// Spawns the talk with hot reloading and moves it to the second node.
+    fn setup(data: TalkData) -> (App, Entity, Handle<TalkData>) {
+        let mut app = talks_minimal_app();
+        let builder = data.fill_builder(TalkBuilder::default());
+        let handle = app.world.resource_mut::<Assets<TalkData>>().add(data);
+        let talk = spawn_built_talk(&mut app, builder);
+        app.world
+            .entity_mut(talk)
+            .insert(HotReloadTalk(handle.clone()));
+        for _ in 0..2 {
+            app.world.send_event(NextNodeRequest::new(talk));
+            app.update();
+        }
+        (app, talk, handle)
+    }
+
+    /// Replaces the asset data and runs the app until the talk is rebuilt.
+    fn modify(app: &mut App, handle: &Handle<TalkData>, data: TalkData) {
+        *app.world
+            .resource_mut::<Assets<TalkData>>()
+            .get_mut(handle)
+            .unwrap() = data;
+        app.update();
+        app.update();
+    }
+
+    const SCRIPT: &str =
+        r#"(id: 1, text: Some("Hello"), next: Some(2)), (id: 2, text: Some("How are you?"))"#;
+
+    #[test]
+    fn reload_keeps_the_current_node() {
+        let (mut app, talk, handle) = setup(talk_data(SCRIPT));
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "How are you?");
+
+        let new_script =
+            r#"(id: 1, text: Some("Hi"), next: Some(2)), (id: 2, text: Some("What's up?"))"#;
+        modify(&mut app, &handle, talk_data(new_script));
+
+        let (text, id, _) = single::<(&TextNode, &NodeId, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "What's up?");
+        assert_eq!(id.0, "2");
+        assert_eq!(count::<&TextNode>(&mut app.world), 2);
+        assert_eq!(count::<With<VisitedNode>>(&mut app.world), 2);
+        assert!(app.world.get::<Talk>(talk).unwrap().has_started);
+        assert_eq!(app.world.get::<Children>(talk).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn reload_goes_back_to_start_if_the_node_is_removed() {
+        let (mut app, talk, handle) = setup(talk_data(SCRIPT));
+
+        modify(&mut app, &handle, talk_data(r#"(id: 1, text: Some("Hi"))"#));
+
+        let (current, _) = single::<(Entity, With<CurrentNode>)>(&mut app.world);
+        assert!(app.world.entity(current).contains::<StartNode>());
+        assert!(!app.world.get::<Talk>(talk).unwrap().has_started);
+        assert_eq!(count::<With<VisitedNode>>(&mut app.world), 1);
+    }
+
+    #[test]
+    fn reload_keeps_the_entry_point() {
+        let mut data = talk_data(SCRIPT);
+        data.entry_points.insert("later".to_string(), 2);
+        let mut app = talks_minimal_app();
+        let handle = app
+            .world
+            .resource_mut::<Assets<TalkData>>()
+            .add(data.clone());
+        let talk = app
+            .world
+            .spawn((Talk::default(), HotReloadTalk(handle.clone())))
+            .id();
+        BuildTalkCommand::new(talk, data.fill_builder(TalkBuilder::default()))
+            .at("later")
+            .apply(&mut app.world);
+
+        data.script[&2].text = "Later".to_string();
+        modify(&mut app, &handle, data);
+
+        let (entry, _) = single::<(&EntryPoint, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(entry.0, "later");
+        assert_eq!(count::<&TextNode>(&mut app.world), 2);
+    }
+
+    #[test]
+    fn talks_without_the_component_are_not_reloaded() {
+        let (mut app, talk, handle) = setup(talk_data(SCRIPT));
+        app.world.entity_mut(talk).remove::<HotReloadTalk>();
+
+        modify(&mut app, &handle, talk_data(r#"(id: 1, text: Some("Hi"))"#));
+
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "How are you?");
+    }
+}
@@ -0,0 +1,948 @@
+//! The Ink Asset Loader, for the stories compiled to JSON by inklecate.
+
+use std::{collections::VecDeque, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    utils::{BoxedFuture, HashMap, HashSet},
+};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    conditions::ConditionId,
+    prelude::{Action, ActionId, Actor, ChoiceData, NodeKind, TalkData},
+    variables::{TalkValue, VariableName},
+};
+
+use super::{
+    script::ScriptBuilder,
+    yarn::{is_speaker, slug},
+};
+
+/// The oldest version of the compiled ink format that can be loaded.
+const MIN_INK_VERSION: i64 = 19;
+
+/// The keys of the object items of a compiled story that are recognized.
+const OBJECT_KEYS: [&str; 12] = [
+    "->", "^->", "temp=", "VAR?", "VAR=", "*", "#", "->t->", "f()", "x()", "CNT?", "list",
+];
+
+/// Load Talks from compiled Ink stories (`.ink.json` files).
+///
+/// The knots, stitches, gathers, choices and diverts become the nodes of the talk graph:
+/// - the lines of text, with an optional speaker prefix (`Ferris: Hello`) that becomes an actor
+/// - the choices, with a variable as condition (`+ {has_key} Open the door`)
+/// - the diverts, and the conditional ones with a variable as condition (`{has_key: -> door}`)
+/// - the global variables (`VAR gold = 0`), set at the start of the talk
+/// - the assignments of literal values (`~ gold = 5`) and increments (`~ gold = gold + 5`)
+/// - the printed variables (`{gold}`), as `{gold}` placeholders
+///
+/// The choices are sticky, like the `+` ink choices: the once-only `*` choices would stay available
+/// after being chosen, so they are an [`InkLoaderError::OnceOnlyChoice`]. Tags are skipped.
+/// Everything else (tunnels, functions, threads, sequences, lists, temporary variables, read counts,
+/// fallback choices and other expressions) is an [`InkLoaderError`].
+pub struct InkLoader;
+
+/// The error type for the Ink Talks loader.
+///
+/// The errors refer to the positions in the compiled story with the ink paths (`knot.stitch.0.3`).
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum InkLoaderError {
+    /// An [IO Error](std::io::Error)
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [JSON Error](serde_json::Error)
+    #[error("Could not parse JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    /// The story was compiled with an unsupported version of ink
+    #[error("the ink version {0} is not supported, the minimum is {MIN_INK_VERSION}")]
+    UnsupportedVersion(i64),
+    /// The JSON is not a compiled ink story
+    #[error("invalid story at {0}: {1}")]
+    InvalidStory(String, &'static str),
+    /// A divert or a choice to a path that is not in the story
+    #[error("at {0}: the path {1} was not found")]
+    InvalidPath(String, String),
+    /// A command that has no equivalent in the talks
+    #[error("at {0}: the command `{1}` is not supported")]
+    UnsupportedCommand(String, String),
+    /// An expression that is not a literal, a variable or an increment of the variable itself
+    #[error("at {0}: {1} is not supported")]
+    UnsupportedExpression(String, String),
+    /// Tunnels, functions, threads and variable diverts
+    #[error("at {0}: {1} are not supported")]
+    UnsupportedDivert(String, &'static str),
+    /// Choices followed by a divert instead of waiting for the player
+    #[error("at {0}: the choices are followed by a divert")]
+    DivertAfterChoices(String),
+    /// Diverts that only lead to other diverts
+    #[error("at {0}: the divert only leads to other diverts endlessly")]
+    EndlessDivert(String),
+    /// Once-only `*` choices, the talks can't hide a choice after it is chosen
+    #[error("at {0}: once-only choices are not supported, use a sticky `+` choice")]
+    OnceOnlyChoice(String),
+}
+
+impl AssetLoader for InkLoader {
+    type Asset = TalkData;
+    type Settings = ();
+    type Error = InkLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            parse_ink(&serde_json::from_slice(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ink.json"]
+    }
+}
+
+/// Converts a compiled ink story into a [`TalkData`].
+pub(crate) fn parse_ink(json: &Value) -> Result<TalkData, InkLoaderError> {
+    let version = json["inkVersion"]
+        .as_i64()
+        .ok_or(InkLoaderError::InvalidStory(
+            String::new(),
+            "missing inkVersion",
+        ))?;
+    if version < MIN_INK_VERSION {
+        return Err(InkLoaderError::UnsupportedVersion(version));
+    }
+    let root = json["root"]
+        .as_array()
+        .filter(|r| !r.is_empty())
+        .ok_or(InkLoaderError::InvalidStory(String::new(), "missing root"))?;
+
+    Compiler::new(Story { root }).compile()
+}
+
+/// A step in the path of a container.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    /// A container in the content of the parent.
+    Index(usize),
+    /// A container in the named content of the parent.
+    Name(String),
+}
+
+/// A position in the story: the index of an item in a container.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Pointer {
+    /// The path of the container.
+    container: Vec<Key>,
+    /// The index of the item in the container.
+    index: usize,
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in &self.container {
+            match key {
+                Key::Index(i) => write!(f, "{i}.")?,
+                Key::Name(n) => write!(f, "{n}.")?,
+            }
+        }
+        write!(f, "{}", self.index)
+    }
+}
+
+/// The root container of a compiled story.
+///
+/// A container is an array with the content followed by an object with the named containers and flags (or null).
+struct Story<'a> {
+    /// The root container.
+    root: &'a [Value],
+}
+
+impl<'a> Story<'a> {
+    /// The container with the given path.
+    fn container(&self, path: &[Key]) -> Option<&'a [Value]> {
+        let mut container = self.root;
+        for key in path {
+            let child = match key {
+                Key::Index(i) => content(container).get(*i),
+                Key::Name(n) => named(container).and_then(|named| named.get(n)),
+            };
+            container = child.and_then(Value::as_array)?;
+        }
+        Some(container)
+    }
+
+    /// The content of the container with the given path, without the named containers.
+    fn content(&self, path: &[Key]) -> &'a [Value] {
+        self.container(path).map_or(&[], content)
+    }
+
+    /// The key of the child container with the given name.
+    /// The containers in the content can be named too.
+    fn child(&self, path: &[Key], name: &str) -> Option<Key> {
+        let container = self.container(path)?;
+        if named(container).is_some_and(|named| named.get(name).is_some_and(Value::is_array)) {
+            return Some(Key::Name(name.to_string()));
+        }
+        content(container)
+            .iter()
+            .position(|item| {
+                item.as_array()
+                    .and_then(|c| named(c))
+                    .is_some_and(|named| named.get("#n").and_then(Value::as_str) == Some(name))
+            })
+            .map(Key::Index)
+    }
+
+    /// Resolves an ink path. Relative paths start from the container of the item at `at`.
+    /// Returns `None` if the path leads to the end of the story.
+    fn resolve(&self, at: &Pointer, path: &str) -> Result<Option<Pointer>, InkLoaderError> {
+        let invalid = || InkLoaderError::InvalidPath(at.to_string(), path.to_string());
+        let (mut keys, components) = match path.strip_prefix('.') {
+            // the first `^` is the container of the item itself
+            Some(relative) => match relative.split_once('.') {
+                Some(("^", rest)) => (at.container.clone(), rest),
+                _ if relative == "^" => (at.container.clone(), ""),
+                _ => return Err(invalid()),
+            },
+            None => (vec![], path),
+        };
+
+        let mut index = None;
+        for component in components.split('.').filter(|c| !c.is_empty()) {
+            if index.is_some() {
+                return Err(invalid());
+            }
+            if component == "^" {
+                keys.pop().ok_or_else(invalid)?;
+            } else if let Ok(i) = component.parse::<usize>() {
+                match self.content(&keys).get(i) {
+                    Some(Value::Array(_)) => keys.push(Key::Index(i)),
+                    Some(_) => index = Some(i),
+                    None => return Err(invalid()),
+                }
+            } else {
+                keys.push(self.child(&keys, component).ok_or_else(invalid)?);
+            }
+        }
+        Ok(self.settle(Pointer {
+            container: keys,
+            index: index.unwrap_or(0),
+        }))
+    }
+
+    /// Moves the pointer to the next item that is not a container, entering the containers
+    /// and leaving them at their end. Returns `None` at the end of the story.
+    ///
+    /// The end of a named container is the end of the story, like in the ink runtime.
+    fn settle(&self, mut pointer: Pointer) -> Option<Pointer> {
+        loop {
+            match self.content(&pointer.container).get(pointer.index) {
+                Some(Value::Array(_)) => {
+                    pointer.container.push(Key::Index(pointer.index));
+                    pointer.index = 0;
+                }
+                Some(_) => return Some(pointer),
+                None => match pointer.container.pop() {
+                    Some(Key::Index(i)) => pointer.index = i + 1,
+                    _ => return None,
+                },
+            }
+        }
+    }
+
+    /// Collects the targets of all the diverts and choices in the container and its children.
+    fn targets(
+        &self,
+        path: Vec<Key>,
+        targets: &mut HashSet<Pointer>,
+    ) -> Result<(), InkLoaderError> {
+        for (index, item) in self.content(&path).iter().enumerate() {
+            let at = Pointer {
+                container: path.clone(),
+                index,
+            };
+            match item {
+                Value::Array(_) => {
+                    let mut child = path.clone();
+                    child.push(Key::Index(index));
+                    self.targets(child, targets)?;
+                }
+                Value::Object(obj) => {
+                    let target = match (obj.get("->"), obj.get("*")) {
+                        (Some(Value::String(p)), _)
+                            if obj.get("var") != Some(&Value::Bool(true)) =>
+                        {
+                            Some(p)
+                        }
+                        (_, Some(Value::String(p))) => Some(p),
+                        _ => None,
+                    };
+                    if let Some(Some(pointer)) = target.map(|p| self.resolve(&at, p)).transpose()? {
+                        targets.insert(pointer);
+                    }
+                }
+                _ => (),
+            }
+        }
+        let names = self.named_children(&path);
+        for name in names {
+            let mut child = path.clone();
+            child.push(Key::Name(name));
+            self.targets(child, targets)?;
+        }
+        Ok(())
+    }
+
+    /// The names of the named containers of the container.
+    fn named_children(&self, path: &[Key]) -> Vec<String> {
+        let named = self.container(path).and_then(named);
+        named.map_or(vec![], |named| {
+            named
+                .iter()
+                .filter(|(_, v)| v.is_array())
+                .map(|(k, _)| k.clone())
+                .collect()
+        })
+    }
+}
+
+/// The content of a container, without the last element.
+fn content(container: &[Value]) -> &[Value] {
+    container.split_last().map_or(&[], |(_, content)| content)
+}
+
+/// The named containers and flags of a container, in its last element.
+fn named(container: &[Value]) -> Option<&Map<String, Value>> {
+    container.last().and_then(Value::as_object)
+}
+
+/// The values of the evaluation stack.
+enum Expr {
+    /// A literal value.
+    Value(TalkValue),
+    /// A variable.
+    Variable(VariableName),
+    /// A variable plus an amount.
+    Add(VariableName, TalkValue),
+    /// A string built with the `str` and `/str` commands.
+    Str(String),
+    /// A divert target.
+    Target(Option<Pointer>),
+}
+
+/// A choice of the story.
+struct InkChoice {
+    /// The text of the choice.
+    text: String,
+    /// The variable or named condition that must hold.
+    condition: Option<ConditionId>,
+    /// Where the story continues if chosen.
+    target: Option<Pointer>,
+}
+
+/// The steps of a straight part of the story, from a divert target to the next divert or end.
+enum Step {
+    /// A line, with the optional speaker.
+    Say(Option<String>, String),
+    /// A variable assignment.
+    Set(VariableName, TalkValue),
+    /// A variable increment.
+    Increment(VariableName, TalkValue),
+    /// A conditional divert.
+    Branch(ConditionId, Option<Pointer>),
+    /// The choices presented at the end of the part.
+    Choices(Vec<InkChoice>),
+    /// A divert at the end of the part.
+    Goto(Option<Pointer>),
+}
+
+/// Turns the story into the actions of a [`TalkData`].
+struct Compiler<'a> {
+    /// The story to compile.
+    story: Story<'a>,
+    /// The script being built.
+    script: ScriptBuilder,
+    /// The speakers of the lines.
+    actors: Vec<Actor>,
+    /// The divert and choice targets. The flow reaching one of them continues in its own part.
+    targets: HashSet<Pointer>,
+    /// The ids reserved for the targets.
+    entries: HashMap<Pointer, ActionId>,
+    /// The targets to compile.
+    queue: VecDeque<Pointer>,
+}
+
+impl<'a> Compiler<'a> {
+    /// Creates a compiler for the story.
+    fn new(story: Story<'a>) -> Self {
+        Self {
+            story,
+            script: ScriptBuilder::default(),
+            actors: vec![],
+            targets: HashSet::default(),
+            entries: HashMap::default(),
+            queue: VecDeque::default(),
+        }
+    }
+
+    /// Compiles all the parts of the story reachable from the start.
+    fn compile(mut self) -> Result<TalkData, InkLoaderError> {
+        self.story.targets(vec![], &mut self.targets)?;
+
+        let root = self.story.settle(Pointer {
+            container: vec![],
+            index: 0,
+        });
+        let start = match self.story.child(&[], "global decl") {
+            // the global variables are set before the start
+            Some(decl) => {
+                let decl = Pointer {
+                    container: vec![decl],
+                    index: 0,
+                };
+                let mut steps = self.walk(decl.clone())?;
+                if !matches!(steps.last(), Some(Step::Goto(_))) {
+                    steps.push(Step::Goto(root));
+                }
+                let entry = self.steps(steps)?;
+                let start = self.script.reserve();
+                self.script.define(start, entry);
+                Some(start)
+            }
+            None => self.entry(root),
+        };
+
+        while let Some(pointer) = self.queue.pop_front() {
+            let steps = self.walk(pointer.clone())?;
+            let entry = self.steps(steps)?;
+            self.script.define(self.entries[&pointer], entry);
+        }
+
+        let entries = self.entries;
+        let script = self.script.finish(start).map_err(|id| {
+            let pointer = entries.iter().find(|(_, &e)| e == id).map(|(p, _)| p);
+            InkLoaderError::EndlessDivert(pointer.map(ToString::to_string).unwrap_or_default())
+        })?;
+        Ok(TalkData::new(script, self.actors))
+    }
+
+    /// The reserved id of a target, queuing it to be compiled the first time.
+    fn entry(&mut self, target: Option<Pointer>) -> Option<ActionId> {
+        let target = target?;
+        if let Some(id) = self.entries.get(&target) {
+            return Some(*id);
+        }
+        let id = self.script.reserve();
+        self.entries.insert(target.clone(), id);
+        self.queue.push_back(target);
+        Some(id)
+    }
+
+    /// Compiles the steps, from the last to the first, and returns the id of the first action.
+    fn steps(&mut self, steps: Vec<Step>) -> Result<Option<ActionId>, InkLoaderError> {
+        let mut next = None;
+        for step in steps.into_iter().rev() {
+            next = match step {
+                Step::Say(speaker, text) => self.script.add(Action {
+                    actors: speaker.iter().map(|s| slug(s)).collect(),
+                    text,
+                    next,
+                    ..Default::default()
+                }),
+                Step::Set(name, value) => self.script.add(Action {
+                    kind: NodeKind::Set(name, value),
+                    next,
+                    ..Default::default()
+                }),
+                Step::Increment(name, amount) => self.script.add(Action {
+                    kind: NodeKind::Increment(name, amount),
+                    next,
+                    ..Default::default()
+                }),
+                Step::Branch(condition, target) => {
+                    let target = self.entry(target);
+                    let choices = vec![
+                        ChoiceData {
+                            next: self.script.target(target),
+                            condition: Some(condition),
+                            ..Default::default()
+                        },
+                        ChoiceData {
+                            next: self.script.target(next),
+                            ..Default::default()
+                        },
+                    ];
+                    self.script.add(Action {
+                        kind: NodeKind::Branch,
+                        choices,
+                        ..Default::default()
+                    })
+                }
+                Step::Choices(choices) => {
+                    let mut data = Vec::with_capacity(choices.len());
+                    for choice in choices {
+                        let target = self.entry(choice.target);
+                        data.push(ChoiceData {
+                            text: choice.text,
+                            next: self.script.target(target),
+                            condition: choice.condition,
+                        });
+                    }
+                    self.script.add(Action {
+                        kind: NodeKind::Choice,
+                        choices: data,
+                        ..Default::default()
+                    })
+                }
+                Step::Goto(target) => self.entry(target),
+            };
+        }
+        Ok(next)
+    }
+
+    /// Follows the flow of the story from the pointer, until the end, a divert or another target.
+    fn walk(&mut self, start: Pointer) -> Result<Vec<Step>, InkLoaderError> {
+        let mut walker = Walker::default();
+        let mut pointer = Some(start);
+        // the targets reached by jumping are not the start of another part
+        let mut jumped = true;
+
+        while let Some(at) = pointer.take() {
+            if !jumped && self.targets.contains(&at) {
+                walker.goto(&at, Some(at.clone()))?;
+                break;
+            }
+            jumped = false;
+            let item = &self.story.content(&at.container)[at.index];
+
+            match walker.item(&self.story, &at, item)? {
+                Flow::Continue => {
+                    pointer = self.story.settle(Pointer {
+                        index: at.index + 1,
+                        ..at
+                    })
+                }
+                Flow::Jump(target) => {
+                    pointer = target;
+                    jumped = true;
+                }
+                Flow::Stop => break,
+            }
+        }
+
+        walker.flush();
+        if !walker.choices.is_empty() {
+            walker.steps.push(Step::Choices(walker.choices));
+        }
+        for step in &walker.steps {
+            if let Step::Say(Some(speaker), _) = step {
+                let slug = slug(speaker);
+                if !self.actors.iter().any(|a| a.slug == slug) {
+                    self.actors.push(Actor::new(slug, speaker.as_str()));
+                }
+            }
+        }
+        Ok(walker.steps)
+    }
+}
+
+/// How the flow goes on after an item.
+enum Flow {
+    /// To the next item.
+    Continue,
+    /// To the given position.
+    Jump(Option<Pointer>),
+    /// The part is over.
+    Stop,
+}
+
+/// The state of the flow while walking a part of the story.
+#[derive(Default)]
+struct Walker {
+    /// The steps of the part.
+    steps: Vec<Step>,
+    /// The text of the current line.
+    text: String,
+    /// If a newline was output after the text.
+    newline: bool,
+    /// If the next text is glued to the current line.
+    glue: bool,
+    /// If inside a tag, that is skipped.
+    in_tag: bool,
+    /// The string being built between `str` and `/str`.
+    string: Option<String>,
+    /// The evaluation stack.
+    stack: Vec<Expr>,
+    /// Where to return after a jump, set with the `$r` temporary variable by the compiler.
+    ret: Option<Option<Pointer>>,
+    /// The choices collected so far.
+    choices: Vec<InkChoice>,
+}
+
+impl Walker {
+    /// Handles an item of the story.
+    fn item(&mut self, story: &Story, at: &Pointer, item: &Value) -> Result<Flow, InkLoaderError> {
+        let unsupported =
+            |cmd: &str| InkLoaderError::UnsupportedCommand(at.to_string(), cmd.into());
+        match item {
+            Value::String(s) if self.in_tag => self.in_tag = s != "/#",
+            Value::String(s) => match s.as_str() {
+                "#" => self.in_tag = true,
+                "\n" if self.string.is_none() => self.newline = true,
+                "\n" | "ev" | "/ev" | "nop" => (),
+                "<>" => self.glue = true,
+                "str" => self.string = Some(String::new()),
+                "/str" => {
+                    let string = self.string.take().ok_or_else(|| unsupported(s))?;
+                    self.stack.push(Expr::Str(string));
+                }
+                "out" => {
+                    let text = match self.pop(at)? {
+                        Expr::Variable(name) => format!("{{{name}}}"),
+                        Expr::Value(value) => value.to_string(),
+                        Expr::Str(string) => string,
+                        _ => return Err(self.expression(at, "printing a divert target")),
+                    };
+                    self.push_text(&text);
+                }
+                "pop" => {
+                    self.pop(at)?;
+                }
+                "+" | "-" => {
+                    let amount = self.pop(at)?;
+                    let expr = match (self.pop(at)?, amount, s.as_str()) {
+                        (Expr::Variable(name), Expr::Value(TalkValue::Int(i)), "+") => {
+                            Expr::Add(name, TalkValue::Int(i))
+                        }
+                        (Expr::Variable(name), Expr::Value(TalkValue::Int(i)), _) => {
+                            Expr::Add(name, TalkValue::Int(-i))
+                        }
+                        (Expr::Variable(name), Expr::Value(TalkValue::Float(f)), "+") => {
+                            Expr::Add(name, TalkValue::Float(f))
+                        }
+                        (Expr::Variable(name), Expr::Value(TalkValue::Float(f)), _) => {
+                            Expr::Add(name, TalkValue::Float(-f))
+                        }
+                        _ => return Err(self.expression(at, &format!("the operator {s}"))),
+                    };
+                    self.stack.push(expr);
+                }
+                "done" | "end" => return Ok(Flow::Stop),
+                text if text.starts_with('^') => self.push_text(&text[1..]),
+                _ => return Err(unsupported(s)),
+            },
+            Value::Bool(b) => self.stack.push(Expr::Value(TalkValue::Bool(*b))),
+            Value::Number(n) => self.stack.push(Expr::Value(match n.as_i64() {
+                Some(i) => TalkValue::Int(i),
+                None => TalkValue::Float(n.as_f64().unwrap_or_default()),
+            })),
+            Value::Object(obj) => return self.object(story, at, obj),
+            Value::Array(_) | Value::Null => {
+                return Err(InkLoaderError::InvalidStory(
+                    at.to_string(),
+                    "unexpected container",
+                ))
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Handles an object item of the story: diverts, choices, variables and tags.
+    fn object(
+        &mut self,
+        story: &Story,
+        at: &Pointer,
+        obj: &Map<String, Value>,
+    ) -> Result<Flow, InkLoaderError> {
+        let divert = |what| InkLoaderError::UnsupportedDivert(at.to_string(), what);
+        let flag = |name| obj.get(name) == Some(&Value::Bool(true));
+        let Some(key) = OBJECT_KEYS.into_iter().find(|k| obj.contains_key(*k)) else {
+            let key = obj.keys().next().cloned().unwrap_or_default();
+            return Err(InkLoaderError::UnsupportedCommand(at.to_string(), key));
+        };
+        let path = obj[key].as_str().unwrap_or_default();
+
+        match key {
+            "->" if flag("var") => match self.ret.take() {
+                Some(target) if path == "$r" => return Ok(Flow::Jump(target)),
+                _ => return Err(divert("variable diverts")),
+            },
+            "->" if flag("c") => {
+                let condition = match self.pop(at)? {
+                    Expr::Variable(name) => name,
+                    _ => return Err(self.expression(at, "a divert condition")),
+                };
+                self.flush();
+                let target = story.resolve(at, path)?;
+                self.steps.push(Step::Branch(condition, target));
+            }
+            // the compiler jumps to the start content of the choices and back
+            "->" if self.ret.is_some() => return Ok(Flow::Jump(story.resolve(at, path)?)),
+            "->" => {
+                self.goto(at, story.resolve(at, path)?)?;
+                return Ok(Flow::Stop);
+            }
+            "^->" => self.stack.push(Expr::Target(story.resolve(at, path)?)),
+            "temp=" if path == "$r" => match self.pop(at)? {
+                Expr::Target(target) => self.ret = Some(target),
+                _ => return Err(self.expression(at, "the temporary variable $r")),
+            },
+            "temp=" => return Err(self.expression(at, "the temporary variables")),
+            "VAR?" => self.stack.push(Expr::Variable(path.to_string())),
+            "VAR=" => {
+                let step = match self.pop(at)? {
+                    Expr::Value(value) => Step::Set(path.to_string(), value),
+                    Expr::Str(string) => Step::Set(path.to_string(), TalkValue::String(string)),
+                    Expr::Add(name, amount) if name == path => Step::Increment(name, amount),
+                    _ => return Err(self.expression(at, &format!("the assignment of {path}"))),
+                };
+                self.flush();
+                self.steps.push(step);
+            }
+            "*" => {
+                let flags = obj.get("flg").and_then(Value::as_u64).unwrap_or_default();
+                if flags & 0x8 != 0 {
+                    return Err(divert("fallback choices"));
+                }
+                if flags & 0x10 != 0 {
+                    return Err(InkLoaderError::OnceOnlyChoice(at.to_string()));
+                }
+                let mut text = String::new();
+                // the evaluation stack has the condition, the start content and the choice only content
+                for content_flag in [0x4, 0x2] {
+                    if flags & content_flag != 0 {
+                        match self.pop(at)? {
+                            Expr::Str(string) => text.insert_str(0, &string),
+                            _ => return Err(self.expression(at, "a choice text")),
+                        }
+                    }
+                }
+                let condition = match flags & 0x1 {
+                    0 => None,
+                    _ => match self.pop(at)? {
+                        Expr::Variable(name) => Some(name),
+                        _ => return Err(self.expression(at, "a choice condition")),
+                    },
+                };
+                self.choices.push(InkChoice {
+                    text: text.trim().to_string(),
+                    condition,
+                    target: story.resolve(at, path)?,
+                });
+            }
+            "#" => (),
+            "->t->" => return Err(divert("tunnels")),
+            "f()" | "x()" => return Err(divert("functions")),
+            "CNT?" => return Err(self.expression(at, "the read counts")),
+            _ => return Err(self.expression(at, "the lists")),
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Ends the part with a divert.
+    fn goto(&mut self, at: &Pointer, target: Option<Pointer>) -> Result<(), InkLoaderError> {
+        if !self.choices.is_empty() {
+            return Err(InkLoaderError::DivertAfterChoices(at.to_string()));
+        }
+        self.flush();
+        self.steps.push(Step::Goto(target));
+        Ok(())
+    }
+
+    /// Adds the text to the string being built or to the current line.
+    fn push_text(&mut self, text: &str) {
+        if let Some(string) = &mut self.string {
+            string.push_str(text);
+            return;
+        }
+        if self.newline && !self.glue {
+            self.flush();
+        }
+        self.newline = false;
+        self.glue = false;
+        self.text.push_str(text);
+    }
+
+    /// Turns the current line into a step.
+    fn flush(&mut self) {
+        let text = self.text.trim();
+        if !text.is_empty() {
+            self.steps.push(match text.split_once(':') {
+                Some((speaker, line)) if is_speaker(speaker) => {
+                    Step::Say(Some(speaker.trim().to_string()), line.trim().to_string())
+                }
+                _ => Step::Say(None, text.to_string()),
+            });
+        }
+        self.text.clear();
+        self.newline = false;
+    }
+
+    /// Pops a value from the evaluation stack.
+    fn pop(&mut self, at: &Pointer) -> Result<Expr, InkLoaderError> {
+        self.stack.pop().ok_or(InkLoaderError::InvalidStory(
+            at.to_string(),
+            "empty evaluation stack",
+        ))
+    }
+
+    /// An error for an unsupported expression.
+    fn expression(&self, at: &Pointer, what: &str) -> InkLoaderError {
+        InkLoaderError::UnsupportedExpression(at.to_string(), what.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::{
+        prelude::*,
+        tests::{spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// The compiled `shop.ink` story of the assets (`shop.ink.json`).
+    fn story() -> Value {
+        serde_json::from_str(include_str!("../../assets/talks/shop.ink.json")).unwrap()
+    }
+
+    /// Builds the talk in an app and returns the app and the talk entity.
+    fn build(data: &TalkData) -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+        (app, talk)
+    }
+
+    /// The text of the current node of the talk, if it has one.
+    fn current_text(app: &mut App) -> Option<String> {
+        app.world
+            .query_filtered::<&TextNode, With<CurrentNode>>()
+            .get_single(&app.world)
+            .ok()
+            .map(|t| t.0.clone())
+    }
+
+    #[test]
+    fn global_variables_are_set_at_the_start() {
+        let data = parse_ink(&story()).unwrap();
+        let (_, first) = data.script.first().unwrap();
+        assert_eq!(
+            first.kind,
+            NodeKind::Set("gold".to_string(), TalkValue::Int(10))
+        );
+        let line = &data.script[&first.next.unwrap()];
+        assert_eq!(line.text, "Hello {gold}!");
+        assert_eq!(line.actors, vec!["ferris".to_string()]);
+    }
+
+    #[test]
+    fn choices_and_diverts() {
+        let data = parse_ink(&story()).unwrap();
+        assert_eq!(
+            data.actors,
+            vec![Actor::new("ferris", "Ferris"), Actor::new("bob", "Bob")]
+        );
+
+        let choice = data
+            .script
+            .values()
+            .find(|a| a.kind == NodeKind::Choice)
+            .unwrap();
+        assert_eq!(choice.choices[0].text, "Buy a sword");
+        assert_eq!(choice.choices[0].condition, None);
+        assert_eq!(choice.choices[1].text, "Leave");
+        assert_eq!(choice.choices[1].condition, Some("rich".to_string()));
+        assert_eq!(data.script[&choice.choices[1].next].text, "Bye.");
+
+        // the start content of the choice is said when chosen
+        let said = &data.script[&choice.choices[0].next];
+        assert_eq!(said.text, "Buy a sword");
+        let increment = &data.script[&said.next.unwrap()];
+        assert_eq!(
+            increment.kind,
+            NodeKind::Increment("gold".to_string(), TalkValue::Int(-5))
+        );
+        assert_eq!(data.script[&increment.next.unwrap()].text, "Thanks!");
+    }
+
+    #[test]
+    fn conditional_divert_is_a_branch() {
+        let story = json!({
+            "inkVersion": 21,
+            "root": [
+                [
+                    "ev", {"VAR?": "has_key"}, "/ev",
+                    [{"->": ".^.b", "c": true}, {"b": ["^Open.", "\n", {"->": "0.4"}, null]}],
+                    "^Done.", "\n", "end", null
+                ],
+                "done", null
+            ]
+        });
+        let data = parse_ink(&story).unwrap();
+        let (_, branch) = data.script.first().unwrap();
+        assert_eq!(branch.kind, NodeKind::Branch);
+        assert_eq!(branch.choices[0].condition, Some("has_key".to_string()));
+
+        let open = &data.script[&branch.choices[0].next];
+        assert_eq!(open.text, "Open.");
+        // both the branch and the fallthrough lead to the same node
+        assert_eq!(open.next, Some(branch.choices[1].next));
+        assert_eq!(data.script[&branch.choices[1].next].text, "Done.");
+    }
+
+    #[test]
+    fn glue_joins_the_lines() {
+        let story = json!({
+            "inkVersion": 21,
+            "root": [["^Hello", "<>", "\n", "^ world", "\n", "^Bye", "\n", "done", null], "done", null]
+        });
+        let data = parse_ink(&story).unwrap();
+        let texts = data
+            .script
+            .values()
+            .map(|a| a.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["Hello world", "Bye"]);
+    }
+
+    #[test]
+    fn traverse_an_ink_talk() {
+        let data = parse_ink(&story()).unwrap();
+        let (mut app, talk) = build(&data);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        assert_eq!(current_text(&mut app).unwrap(), "Hello {gold}!");
+        let talk_vars = app.world.get::<TalkVariables>(talk).unwrap();
+        assert_eq!(talk_vars.0["gold"], TalkValue::Int(10));
+    }
+
+    #[rstest]
+    #[case(json!({"inkVersion": 18, "root": [null]}), "UnsupportedVersion(18)")]
+    #[case(json!({"root": [null]}), "InvalidStory")]
+    #[case(json!({"inkVersion": 21, "root": [["^a", {"->": "missing"}, null], null]}), "InvalidPath(\"0.1\", \"missing\")")]
+    #[case(json!({"inkVersion": 21, "root": [["visit", null], null]}), "UnsupportedCommand(\"0.0\", \"visit\")")]
+    #[case(json!({"inkVersion": 21, "root": [["ev", {"CNT?": ".^"}, "/ev", null], null]}), "UnsupportedExpression(\"0.1\", \"the read counts\")")]
+    #[case(json!({"inkVersion": 21, "root": [["ev", {"VAR?": "a"}, 2, "*", null], null]}), "UnsupportedCommand(\"0.3\", \"*\")")]
+    #[case(json!({"inkVersion": 21, "root": [[{"->t->": "k"}, null], {"k": [null]}]}), "UnsupportedDivert(\"0.0\", \"tunnels\")")]
+    #[case(json!({"inkVersion": 21, "root": [[{"->": "x", "var": true}, null], null]}), "UnsupportedDivert(\"0.0\", \"variable diverts\")")]
+    #[case(json!({"inkVersion": 21, "root": [[{"*": "0.c-0", "flg": 8}, {"c-0": ["done", null]}], null]}), "UnsupportedDivert(\"0.0\", \"fallback choices\")")]
+    #[case(json!({"inkVersion": 21, "root": [[{"*": "0.c-0", "flg": 16}, {"c-0": ["done", null]}], null]}), "OnceOnlyChoice(\"0.0\")")]
+    #[case(json!({"inkVersion": 21, "root": [[{"->": "a"}, null], {"a": [{"->": "b"}, null], "b": [{"->": "a"}, null]}]}), "EndlessDivert")]
+    fn unsupported_constructs_are_errors(#[case] story: Value, #[case] error: &str) {
+        let err = parse_ink(&story).unwrap_err();
+        assert!(format!("{err:?}").starts_with(error), "{err:?}");
+    }
+}
@@ -0,0 +1,113 @@
+//! The json Asset Loader, with the same schema as the talk.ron files.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::{AppTypeRegistry, FromWorld, World},
+    reflect::{TypeRegistry, TypeRegistryArc},
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeSeed;
+
+use crate::prelude::{TalkData, TalksLoaderSettings};
+
+use super::loader::{load_with, parse_with, RonLoaderError};
+
+/// Load Talks from json assets (`.talk.json` files).
+pub struct JsonTalksLoader {
+    /// The type registry of the app.
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for JsonTalksLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+        }
+    }
+}
+
+impl AssetLoader for JsonTalksLoader {
+    type Asset = TalkData;
+    type Settings = TalksLoaderSettings;
+    type Error = RonLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(load_with(reader, settings, load_context, |bytes| {
+            parse_json(bytes, &self.type_registry.read())
+        }))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["talk.json"]
+    }
+}
+
+/// Parses and validates the content of a talk.json file.
+pub(crate) fn parse_json(
+    bytes: &[u8],
+    registry: &TypeRegistry,
+) -> Result<TalkData, RonLoaderError> {
+    parse_with(bytes, registry, |bytes, seed| {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        let talk = seed.deserialize(&mut deserializer)?;
+        deserializer.end().map(|_| talk)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ron_loader::loader::parse_ron;
+
+    use super::*;
+
+    #[test]
+    fn parse_the_same_schema_as_ron() {
+        let registry = TypeRegistry::default();
+        let json = r#"{
+            "actors": [{ "slug": "ferris", "name": "Ferris" }],
+            "script": [
+                { "id": 1, "actors": ["ferris"], "text": "Hello", "next": 2 },
+                { "id": 2, "action": { "Set": ["gold", 5] }, "next": 3 },
+                { "id": 3, "choices": [
+                    { "text": "Buy", "next": 4, "condition": "rich" },
+                    { "text": "Leave", "next": 5 }
+                ]},
+                { "id": 4, "branches": [{ "condition": "met_ferris", "next": 5 }, { "next": 5 }] },
+                { "id": 5, "action": "Leave", "actors": ["ferris"] }
+            ]
+        }"#;
+        let ron = r#"(
+            actors: [(slug: "ferris", name: "Ferris")],
+            script: [
+                (id: 1, actors: ["ferris"], text: Some("Hello"), next: Some(2)),
+                (id: 2, action: Set("gold", 5), next: Some(3)),
+                (id: 3, choices: Some([
+                    (text: "Buy", next: 4, condition: Some("rich")),
+                    (text: "Leave", next: 5),
+                ])),
+                (id: 4, branches: Some([(condition: Some("met_ferris"), next: 5), (next: 5)])),
+                (id: 5, action: Leave, actors: ["ferris"]),
+            ],
+        )"#;
+        assert_eq!(
+            parse_json(json.as_bytes(), &registry).unwrap(),
+            parse_ron(ron.as_bytes(), &registry).unwrap()
+        );
+    }
+
+    #[test]
+    fn validate_like_ron() {
+        let registry = TypeRegistry::default();
+        let json = r#"{ "actors": [], "script": [{ "id": 1, "actors": ["ferris"] }] }"#;
+        let err = parse_json(json.as_bytes(), &registry).unwrap_err();
+        assert!(matches!(err, RonLoaderError::InvalidActorSlug(s) if s == "ferris"));
+
+        let err = parse_json(b"{ \"actors\": [] }", &registry).unwrap_err();
+        assert!(matches!(err, RonLoaderError::JsonError(_)));
+    }
+}
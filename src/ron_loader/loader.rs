@@ -2,21 +2,34 @@
 
 use bevy::{
     asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
-    log::error,
-    utils::{hashbrown::HashSet, BoxedFuture},
+    prelude::{AppTypeRegistry, FromWorld, World},
+    reflect::{TypeRegistry, TypeRegistryArc},
+    utils::BoxedFuture,
 };
-use indexmap::IndexMap;
-use serde_ron::de::from_bytes;
+use serde_ron::Options;
 use thiserror::Error;
 
-use crate::prelude::{Action, ActionId, Actor, ActorSlug, TalkData};
+use crate::prelude::{ActionId, ActorSlug, TalkData, TalkLintReport, TalksLoaderSettings};
 
-use super::types::RonTalk;
+use super::{seed::TalkSeed, types::RonTalk, validation::validate_talk};
 
-/// Load Talks from json assets.
-pub struct TalksLoader;
+/// Load Talks from ron assets.
+///
+/// The custom components of the actions are deserialized with the app type registry.
+pub struct TalksLoader {
+    /// The type registry of the app.
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for TalksLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+        }
+    }
+}
 
-/// The error type for the RON Talks loader.
+/// The error type for the Talks loaders (RON, and JSON and YAML with their features).
 #[non_exhaustive]
 #[derive(Debug, Error)]
 pub enum RonLoaderError {
@@ -26,6 +39,14 @@ pub enum RonLoaderError {
     /// A [RON Error](serde_ron::error::SpannedError)
     #[error("Could not parse RON: {0}")]
     RonError(#[from] serde_ron::error::SpannedError),
+    /// A [JSON Error](serde_json::Error)
+    #[cfg(feature = "json")]
+    #[error("Could not parse JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    /// A [YAML Error](serde_yaml::Error)
+    #[cfg(feature = "yaml")]
+    #[error("Could not parse YAML: {0}")]
+    YamlError(#[from] serde_yaml::Error),
     /// Multiple actions have same id error
     #[error("multiple actions have same id: {0}")]
     DuplicateActionId(ActionId),
@@ -38,63 +59,34 @@ pub enum RonLoaderError {
     /// An action has a non-existent actor
     #[error("An action is performed by actor {0}, but it was not defined in the actors.")]
     InvalidActorSlug(ActorSlug),
+    /// An entry point starts from a non-existent action
+    #[error("the entry point {0} is pointing to id {1} which was not found")]
+    InvalidEntryPoint(String, ActionId),
+    /// More than one action is a start action
+    #[error("the actions {0} and {1} are both start actions, a talk has only one")]
+    MultipleStartActions(ActionId, ActionId),
+    /// An action has a timeout but it is not a choice action, or its default choice is out of range
+    #[error("the action {0} has a timeout, but it is not a choice or its default choice is out of range")]
+    InvalidTimeout(ActionId),
+    /// The talk has lint errors, and the loader settings deny them
+    #[error("the talk has lint errors: {0}")]
+    LintErrors(TalkLintReport),
 }
 
 impl AssetLoader for TalksLoader {
     type Asset = TalkData;
-    type Settings = ();
+    type Settings = TalksLoaderSettings;
     type Error = RonLoaderError;
 
     fn load<'a>(
         &'a self,
         reader: &'a mut Reader,
-        _settings: &'a Self::Settings,
-        _load_context: &'a mut LoadContext,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
-        Box::pin(async move {
-            let mut bytes = Vec::new();
-            reader.read_to_end(&mut bytes).await?;
-            let ron_talk = from_bytes::<RonTalk>(&bytes)?;
-
-            // build a TalkData Asset from the RonTalk
-
-            // 1. Build the actors vec
-            let actors = ron_talk.actors;
-            let mut talk_actors = Vec::<Actor>::with_capacity(actors.len());
-
-            let mut slug_set = HashSet::<ActorSlug>::with_capacity(actors.len());
-
-            // let mut asset_deps = vec![];
-            for actor in actors {
-                let slug = actor.slug.clone();
-
-                if !slug_set.insert(slug.clone()) {
-                    return Err(RonLoaderError::DuplicateActorSlug(slug));
-                }
-                let talk_actor = Actor::new(slug.clone(), actor.name);
-                talk_actors.push(talk_actor)
-            }
-
-            // 2. build the raw_actions vec
-            let mut raw_actions =
-                IndexMap::<ActionId, Action>::with_capacity(ron_talk.script.len());
-            for action in ron_talk.script {
-                let id = action.id;
-                if raw_actions.insert(id, action.into()).is_some() {
-                    return Err(RonLoaderError::DuplicateActionId(id));
-                }
-            }
-
-            validate_all_nexts(&raw_actions)?; // check if all nexts point to real actions
-            validate_actors(slug_set, &raw_actions)?;
-
-            let raw_talk = TalkData {
-                actors: talk_actors,
-                script: raw_actions,
-            };
-
-            Ok(raw_talk)
-        })
+        Box::pin(load_with(reader, settings, load_context, |bytes| {
+            parse_ron(bytes, &self.type_registry.read())
+        }))
     }
 
     fn extensions(&self) -> &[&str] {
@@ -102,50 +94,54 @@ impl AssetLoader for TalksLoader {
     }
 }
 
-/// Check if the actions use only actors that are defined in the talk.
-fn validate_actors(
-    actor_slugs: HashSet<ActorSlug>,
-    actions: &IndexMap<ActionId, Action>,
-) -> Result<(), RonLoaderError> {
-    for action in actions.values() {
-        for slug in action.actors.iter() {
-            if !actor_slugs.contains(slug) {
-                return Err(RonLoaderError::InvalidActorSlug(slug.clone()));
-            }
-        }
+impl TalkData {
+    /// Loads the portraits and the voices of the actors as dependencies of the talk asset.
+    pub(crate) fn load_actor_assets(mut self, load_context: &mut LoadContext) -> Self {
+        let paths = self.actors.iter().flat_map(|a| a.asset_paths());
+        self.actor_assets = paths.map(|p| load_context.load_untyped(p)).collect();
+        self
     }
-    Ok(())
 }
 
-/// Check if all `next` fields and `Choice` `next` fields in a `Vec<RawAction>` point to real actions.
-/// If the action has choices, the `next` field is not checked.
-///
-/// Returns a `TalkError::InvalidNextAction` error if any of the `next` fields or `Choice` `next` fields in the `RawAction`s do not point to real actions.
-fn validate_all_nexts(actions: &IndexMap<ActionId, Action>) -> Result<(), RonLoaderError> {
-    let id_set = actions.keys().cloned().collect::<HashSet<_>>();
-    for (id, action) in actions {
-        if !action.choices.is_empty() {
-            for choice in action.choices.iter() {
-                if !id_set.contains(&choice.next) {
-                    return Err(RonLoaderError::InvalidNextAction(*id, choice.next));
-                }
-            }
-        } else if let Some(next_id) = &action.next {
-            if !id_set.contains(next_id) {
-                return Err(RonLoaderError::InvalidNextAction(*id, *next_id));
-            }
-        }
-    }
-    Ok(())
+/// Reads a talk file, parses it with the given function and loads the assets of the actors,
+/// then checks the lints as set in the loader settings. It is the loading of the RON, JSON and YAML loaders.
+pub(crate) async fn load_with(
+    reader: &mut Reader<'_>,
+    settings: &TalksLoaderSettings,
+    load_context: &mut LoadContext<'_>,
+    parse: impl FnOnce(&[u8]) -> Result<TalkData, RonLoaderError>,
+) -> Result<TalkData, RonLoaderError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    let talk = parse(&bytes)?.load_actor_assets(load_context);
+    settings.lint.check(talk, load_context.path())
+}
+
+/// Parses and validates a talk with the schema of the talk.ron files,
+/// deserializing the bytes with the seed of the type registry in the given format.
+pub(crate) fn parse_with<E>(
+    bytes: &[u8],
+    registry: &TypeRegistry,
+    deserialize: impl FnOnce(&[u8], TalkSeed) -> Result<RonTalk, E>,
+) -> Result<TalkData, RonLoaderError>
+where
+    RonLoaderError: From<E>,
+{
+    validate_talk(deserialize(bytes, TalkSeed(registry))?)
+}
+
+/// Parses and validates the content of a talk.ron file.
+pub(crate) fn parse_ron(bytes: &[u8], registry: &TypeRegistry) -> Result<TalkData, RonLoaderError> {
+    parse_with(bytes, registry, |bytes, seed| {
+        Options::default().from_bytes_seed(bytes, seed)
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use indexmap::indexmap;
-
     use bevy::prelude::*;
 
-    use crate::prelude::*;
+    use crate::{prelude::*, tests::single, tests::spawn_built_talk, tests::talks_minimal_app};
 
     use super::*;
 
@@ -175,67 +171,148 @@ mod tests {
     // }
 
     #[test]
-    fn error_invalid_next_action() {
-        let talk = TalkData {
-            script: indexmap! {0 => Action {
-                next: Some(2),
-                ..default()
-            }},
-            ..default()
-        };
-        let res = validate_all_nexts(&talk.script);
-        assert!(res.is_err());
+    fn parse_set_and_increment_actions() {
+        let talk = parse_ron(
+            br#"(actors: [], script: [
+                (id: 1, action: Set("met_ferris", true), next: Some(2)),
+                (id: 2, action: Increment("global.gold", -5)),
+            ])"#,
+            &TypeRegistry::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            talk.script[&1].kind,
+            NodeKind::Set("met_ferris".to_string(), TalkValue::Bool(true))
+        );
+        assert_eq!(
+            talk.script[&2].kind,
+            NodeKind::Increment("global.gold".to_string(), TalkValue::Int(-5))
+        );
     }
 
     #[test]
-    fn error_not_found_in_choice() {
-        let talk = TalkData {
-            actors: default(),
-            script: indexmap! {
-                0 => Action {
-                    choices: vec![ChoiceData { next: 2, ..default()}],
-                    ..default()
-                },
-                1 => Action {
-                    ..default()
-                },
-            },
-        };
-        let res = validate_all_nexts(&talk.script);
-        assert!(res.is_err());
+    fn error_duplicate_action_id() {
+        let talk = br#"(actors: [], script: [(id: 1), (id: 1)])"#;
+        let res = parse_ron(talk, &TypeRegistry::default());
+        assert!(matches!(res, Err(RonLoaderError::DuplicateActionId(1))));
     }
 
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct CameraShake {
+        strength: f32,
+    }
+
+    #[derive(Reflect, Default)]
+    struct NotAComponent;
+
+    /// A talk with a `CameraShake` component in the first action.
+    const SHAKE: &[u8] = br#"(
+        actors: [],
+        script: [
+            (
+                id: 1,
+                text: Some("Boom!"),
+                components: [
+                    { "bevy_talks::ron_loader::loader::tests::CameraShake": (strength: 2.5) },
+                ],
+            ),
+        ],
+    )"#;
+
+    #[test]
+    fn components_are_built_in_the_node() {
+        let mut app = talks_minimal_app();
+        app.register_type::<CameraShake>();
+
+        let data = parse_ron(SHAKE, &app.world.resource::<AppTypeRegistry>().read()).unwrap();
+        assert_eq!(data.script[&1].components.0.len(), 1);
+
+        spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+
+        let (text, shake) = single::<(&TextNode, &CameraShake)>(&mut app.world);
+        assert_eq!(text.0, "Boom!");
+        assert_eq!(*shake, CameraShake { strength: 2.5 });
+    }
+
+    #[test]
+    fn components_are_written_back() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<CameraShake>();
+
+        let data = parse_ron(SHAKE, &registry).unwrap();
+        let saved = data.to_ron(&registry).unwrap();
+        assert!(saved.contains("CameraShake"));
+        assert_eq!(parse_ron(saved.as_bytes(), &registry).unwrap(), data);
+    }
+
+    #[test]
+    fn error_unregistered_component() {
+        let err = parse_ron(SHAKE, &TypeRegistry::default()).unwrap_err();
+        assert!(err.to_string().contains("CameraShake"));
+
+        let mut registry = TypeRegistry::default();
+        registry.register::<NotAComponent>();
+        let talk = br#"(actors: [], script: [(id: 1, components: [
+            { "bevy_talks::ron_loader::loader::tests::NotAComponent": () },
+        ])])"#;
+        let err = parse_ron(talk, &registry).unwrap_err();
+        assert!(err.to_string().contains("NotAComponent is not registered"));
+    }
+
+    /// A talk with an actor with a portrait, a voice and a `CameraShake` component.
+    const ACTOR_METADATA: &[u8] = br#"(
+        actors: [
+            (
+                slug: "ferris",
+                name: "Ferris",
+                portrait: Some("images/ferris.png"),
+                voice: Some("sounds/blip.ogg"),
+                components: [
+                    { "bevy_talks::ron_loader::loader::tests::CameraShake": (strength: 1.0) },
+                ],
+            ),
+        ],
+        script: [(id: 1, actors: ["ferris"], text: Some("Hello"))],
+    )"#;
+
     #[test]
-    fn test_validate_actors_valid() {
-        let mut actor_slugs = HashSet::<ActorSlug>::new();
-        actor_slugs.insert("actor1".to_string());
-        actor_slugs.insert("actor2".to_string());
-
-        let actions = indexmap! {
-            0 => Action {
-                actors: vec!["actor1".to_string()],
-                ..default()
-            },
-            1 => Action {
-                actors: vec!["actor1".to_string(), "actor2".to_string()],
-                ..default()
-            },
-        };
-        assert!(validate_actors(actor_slugs, &actions).is_ok());
+    fn actor_metadata_is_built_in_the_actor() {
+        let mut app = talks_minimal_app();
+        app.register_type::<CameraShake>();
+
+        let registry = app.world.resource::<AppTypeRegistry>().clone();
+        let data = parse_ron(ACTOR_METADATA, &registry.read()).unwrap();
+        let talk = spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+
+        let (actor, shake) = single::<(&Actor, &CameraShake)>(&mut app.world);
+        assert_eq!(actor.portrait.as_deref(), Some("images/ferris.png"));
+        assert_eq!(actor.voice.as_deref(), Some("sounds/blip.ogg"));
+        assert_eq!(*shake, CameraShake { strength: 1.0 });
+        let actor = actor.clone();
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let events = app.world.resource::<Events<TextNodeEvent>>();
+        let speakers = events
+            .get_reader()
+            .read(events)
+            .next()
+            .unwrap()
+            .speakers
+            .clone();
+        assert_eq!(speakers, vec![actor]);
     }
 
     #[test]
-    fn test_validate_actors_invalid() {
-        let mut actor_slugs = HashSet::<ActorSlug>::new();
-        actor_slugs.insert("actor1".to_string());
-        actor_slugs.insert("actor2".to_string());
-        let actions = indexmap! {
-            0 => Action {
-                actors: vec!["actor3".to_string()],
-                ..default()
-            },
-        };
-        let result = validate_actors(actor_slugs, &actions);
-        assert!(result.is_err());
+    fn actor_metadata_is_written_back() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<CameraShake>();
+
+        let data = parse_ron(ACTOR_METADATA, &registry).unwrap();
+        assert_eq!(data.actor_components["ferris"].0.len(), 1);
+        let saved = data.to_ron(&registry).unwrap();
+        assert!(saved.contains("portrait: Some(\"images/ferris.png\")"));
+        assert_eq!(parse_ron(saved.as_bytes(), &registry).unwrap(), data);
     }
 }
@@ -1,4 +1,15 @@
-//! Asset loader for Talks from "talks.ron" files.
+//! Asset loaders for Talks from "talk.ron" (or "talk.json" and "talk.yaml"), Yarn Spinner ".yarn" and compiled Ink ".ink.json" files.
 
+#[cfg(feature = "ink")]
+pub(crate) mod ink;
+#[cfg(feature = "json")]
+pub(crate) mod json;
 pub(crate) mod loader;
+mod script;
+mod seed;
 mod types;
+mod validation;
+mod writer;
+#[cfg(feature = "yaml")]
+pub(crate) mod yaml;
+pub(crate) mod yarn;
@@ -0,0 +1,109 @@
+//! The script builder shared by the loaders that compile other dialogue formats into talk actions.
+
+use bevy::utils::HashMap;
+use indexmap::IndexMap;
+
+use crate::prelude::{Action, ActionId, NodeKind};
+
+/// Builds the actions of a talk script.
+///
+/// The jump targets can be referenced before they are compiled with reserved ids,
+/// that are replaced with the actions they stand for when the script is finished.
+#[derive(Default)]
+pub(crate) struct ScriptBuilder {
+    /// The compiled actions.
+    actions: IndexMap<ActionId, Action>,
+    /// The last used action id.
+    last_id: ActionId,
+    /// The reserved ids, with the action they stand for (that can be another reserved id).
+    /// `None` means the end of the talk.
+    reserved: HashMap<ActionId, Option<ActionId>>,
+    /// The empty action that ends the talk, for the choices and branches that lead nowhere.
+    end: Option<ActionId>,
+}
+
+impl ScriptBuilder {
+    /// Reserves an id for an action that is not compiled yet.
+    pub(crate) fn reserve(&mut self) -> ActionId {
+        let id = self.next_id();
+        self.reserved.insert(id, None);
+        id
+    }
+
+    /// Sets the action a reserved id stands for.
+    pub(crate) fn define(&mut self, reserved: ActionId, action: Option<ActionId>) {
+        self.reserved.insert(reserved, action);
+    }
+
+    /// Adds the action with a new id.
+    pub(crate) fn add(&mut self, action: Action) -> Option<ActionId> {
+        let id = self.next_id();
+        self.actions.insert(id, action);
+        Some(id)
+    }
+
+    /// The target of a choice or a branch: the given action or, if missing, the empty end action.
+    pub(crate) fn target(&mut self, action: Option<ActionId>) -> ActionId {
+        if let Some(id) = action.or(self.end) {
+            return id;
+        }
+        let end = self.add(Action {
+            kind: NodeKind::Empty,
+            ..Default::default()
+        });
+        self.end = end;
+        end.expect("end action")
+    }
+
+    /// Replaces the reserved ids with the actions they stand for and puts the start action first.
+    ///
+    /// # Errors
+    /// The reserved id that only leads to other reserved ids endlessly.
+    pub(crate) fn finish(
+        mut self,
+        start: Option<ActionId>,
+    ) -> Result<IndexMap<ActionId, Action>, ActionId> {
+        let mut ids = self.reserved.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+
+        let mut resolved = HashMap::with_capacity(ids.len());
+        for id in ids {
+            let mut action = Some(id);
+            for _ in 0..=self.reserved.len() {
+                match action {
+                    Some(a) if self.reserved.contains_key(&a) => action = self.reserved[&a],
+                    _ => break,
+                }
+            }
+            if action.is_some_and(|a| self.reserved.contains_key(&a)) {
+                return Err(id);
+            }
+            resolved.insert(id, action);
+        }
+
+        let targets = resolved
+            .iter()
+            .map(|(&id, &action)| (id, self.target(action)))
+            .collect::<HashMap<_, _>>();
+        for action in self.actions.values_mut() {
+            if let Some(next) = action.next.and_then(|n| resolved.get(&n)) {
+                action.next = *next;
+            }
+            for choice in action.choices.iter_mut() {
+                choice.next = targets.get(&choice.next).copied().unwrap_or(choice.next);
+            }
+        }
+
+        if let Some(start) = start.and_then(|s| targets.get(&s).or(Some(&s)).copied()) {
+            let index = self.actions.get_index_of(&start).expect("start action");
+            self.actions.move_index(index, 0);
+        }
+        Ok(self.actions)
+    }
+
+    /// The next unused action id.
+    fn next_id(&mut self) -> ActionId {
+        self.last_id += 1;
+        self.last_id
+    }
+}
@@ -0,0 +1,297 @@
+//! The deserialization of the talk files with the type registry, to load the reflected components of the actions.
+//!
+//! The seeds work with any serde format, so the RON, JSON and YAML files share them.
+
+use std::fmt;
+
+use bevy::reflect::{serde::UntypedReflectDeserializer, Reflect, TypeRegistry};
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::builder::build_command::reflect_component;
+
+use super::types::{RonAction, RonActor, RonTalk};
+
+/// The fields of a talk file.
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum TalkField {
+    /// The `actors` field.
+    Actors,
+    /// The `script` field.
+    Script,
+    /// The `entry_points` field.
+    EntryPoints,
+    /// Any other field, ignored.
+    #[serde(other)]
+    Other,
+}
+
+/// The fields of an action.
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum ActionField {
+    /// The `id` field.
+    Id,
+    /// The `action` field.
+    Action,
+    /// The `actors` field.
+    Actors,
+    /// The `text` field.
+    Text,
+    /// The `choices` field.
+    Choices,
+    /// The `timeout` field.
+    Timeout,
+    /// The `branches` field.
+    Branches,
+    /// The `next` field.
+    Next,
+    /// The `auto_advance` field.
+    AutoAdvance,
+    /// The `components` field.
+    Components,
+    /// Any other field, ignored.
+    #[serde(other)]
+    Other,
+}
+
+/// The fields of an actor.
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum ActorField {
+    /// The `slug` field.
+    Slug,
+    /// The `name` field.
+    Name,
+    /// The `portrait` field.
+    Portrait,
+    /// The `voice` field.
+    Voice,
+    /// The `components` field.
+    Components,
+    /// Any other field, ignored.
+    #[serde(other)]
+    Other,
+}
+
+/// Deserializes a [`RonTalk`], with the components of the actions and actors resolved through the type registry.
+pub(crate) struct TalkSeed<'a>(pub(crate) &'a TypeRegistry);
+
+impl<'de> DeserializeSeed<'de> for TalkSeed<'_> {
+    type Value = RonTalk;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("RonTalk", &["actors", "script", "entry_points"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for TalkSeed<'_> {
+    type Value = RonTalk;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a talk with actors and script")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut actors, mut script, mut entry_points) = (None, None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                TalkField::Actors => actors = Some(map.next_value_seed(ActorsSeed(self.0))?),
+                TalkField::Script => script = Some(map.next_value_seed(ScriptSeed(self.0))?),
+                TalkField::EntryPoints => entry_points = Some(map.next_value()?),
+                TalkField::Other => _ = map.next_value::<IgnoredAny>()?,
+            }
+        }
+        Ok(RonTalk {
+            actors: actors.ok_or_else(|| de::Error::missing_field("actors"))?,
+            script: script.ok_or_else(|| de::Error::missing_field("script"))?,
+            entry_points: entry_points.unwrap_or_default(),
+        })
+    }
+}
+
+/// Deserializes the list of actors of a talk.
+struct ActorsSeed<'a>(&'a TypeRegistry);
+
+impl<'de> DeserializeSeed<'de> for ActorsSeed<'_> {
+    type Value = Vec<RonActor>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ActorsSeed<'_> {
+    type Value = Vec<RonActor>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of actors")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut actors = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(actor) = seq.next_element_seed(ActorSeed(self.0))? {
+            actors.push(actor);
+        }
+        Ok(actors)
+    }
+}
+
+/// Deserializes an actor. The slug and the name are required.
+struct ActorSeed<'a>(&'a TypeRegistry);
+
+impl<'de> DeserializeSeed<'de> for ActorSeed<'_> {
+    type Value = RonActor;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct(
+            "RonActor",
+            &["slug", "name", "portrait", "voice", "components"],
+            self,
+        )
+    }
+}
+
+impl<'de> Visitor<'de> for ActorSeed<'_> {
+    type Value = RonActor;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an actor with a slug and a name")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut slug, mut name) = (None, None);
+        let mut actor = RonActor::default();
+        while let Some(field) = map.next_key()? {
+            match field {
+                ActorField::Slug => slug = Some(map.next_value()?),
+                ActorField::Name => name = Some(map.next_value()?),
+                ActorField::Portrait => actor.portrait = map.next_value()?,
+                ActorField::Voice => actor.voice = map.next_value()?,
+                ActorField::Components => {
+                    actor.components = map.next_value_seed(ComponentsSeed(self.0))?
+                }
+                ActorField::Other => _ = map.next_value::<IgnoredAny>()?,
+            }
+        }
+        actor.slug = slug.ok_or_else(|| de::Error::missing_field("slug"))?;
+        actor.name = name.ok_or_else(|| de::Error::missing_field("name"))?;
+        Ok(actor)
+    }
+}
+
+/// Deserializes the list of actions of a talk.
+struct ScriptSeed<'a>(&'a TypeRegistry);
+
+impl<'de> DeserializeSeed<'de> for ScriptSeed<'_> {
+    type Value = Vec<RonAction>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ScriptSeed<'_> {
+    type Value = Vec<RonAction>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of actions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut actions = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(action) = seq.next_element_seed(ActionSeed(self.0))? {
+            actions.push(action);
+        }
+        Ok(actions)
+    }
+}
+
+/// Deserializes an action. The fields are optional, except the id.
+struct ActionSeed<'a>(&'a TypeRegistry);
+
+/// The fields of an action, in the order they are written.
+const ACTION_FIELDS: &[&str] = &[
+    "id",
+    "action",
+    "actors",
+    "text",
+    "choices",
+    "timeout",
+    "branches",
+    "next",
+    "auto_advance",
+    "components",
+];
+
+impl<'de> DeserializeSeed<'de> for ActionSeed<'_> {
+    type Value = RonAction;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("RonAction", ACTION_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for ActionSeed<'_> {
+    type Value = RonAction;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an action with an id")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut id = None;
+        let mut action = RonAction::default();
+        while let Some(field) = map.next_key()? {
+            match field {
+                ActionField::Id => id = Some(map.next_value()?),
+                ActionField::Action => action.action = map.next_value()?,
+                ActionField::Actors => action.actors = map.next_value()?,
+                ActionField::Text => action.text = map.next_value()?,
+                ActionField::Choices => action.choices = map.next_value()?,
+                ActionField::Timeout => action.timeout = map.next_value()?,
+                ActionField::Branches => action.branches = map.next_value()?,
+                ActionField::Next => action.next = map.next_value()?,
+                ActionField::AutoAdvance => action.auto_advance = map.next_value()?,
+                ActionField::Components => {
+                    action.components = map.next_value_seed(ComponentsSeed(self.0))?
+                }
+                ActionField::Other => _ = map.next_value::<IgnoredAny>()?,
+            }
+        }
+        action.id = id.ok_or_else(|| de::Error::missing_field("id"))?;
+        Ok(action)
+    }
+}
+
+/// Deserializes the components of an action or an actor, checking that they are registered components.
+struct ComponentsSeed<'a>(&'a TypeRegistry);
+
+impl<'de> DeserializeSeed<'de> for ComponentsSeed<'_> {
+    type Value = Vec<Box<dyn Reflect>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentsSeed<'_> {
+    type Value = Vec<Box<dyn Reflect>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of reflected components")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut components = vec![];
+        let deserializer = || UntypedReflectDeserializer::new(self.0);
+        while let Some(component) = seq.next_element_seed(deserializer())? {
+            reflect_component(&*component, self.0).map_err(de::Error::custom)?;
+            components.push(component);
+        }
+        Ok(components)
+    }
+}
@@ -1,18 +1,29 @@
 //! Types used by the ron loader.
 
-use serde::Deserialize;
+use bevy::reflect::{serde::ReflectSerializer, Reflect, TypeRegistry};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 
-use crate::prelude::{Action, ActionId, ActorSlug, ChoiceData, NodeKind};
+use crate::{
+    conditions::ConditionId,
+    prelude::{Action, ActionId, Actor, ActorSlug, ChoiceData, ChoiceTimeout, NodeKind, TalkData},
+    talk_asset::ActionComponents,
+};
 
 /// The ron talk asset type.
 ///
 /// It contains a list of actors that appear in the Talk, and a list of actions that make up the Talk.
-#[derive(Deserialize, Debug)]
-pub(crate) struct RonTalk {
+/// It is deserialized with the type registry (see [`super::seed::TalkSeed`]) to load the components of the actions and actors,
+/// and serialized with the components wrapped in `ReflectSerializer`s.
+#[derive(Serialize, Debug)]
+pub(crate) struct RonTalk<C = Box<dyn Reflect>> {
     /// The list of actors that appear in the Talk.
-    pub(crate) actors: Vec<RonActor>,
+    pub(crate) actors: Vec<RonActor<C>>,
     /// The list of actions that make up the Talk.
-    pub(crate) script: Vec<RonAction>,
+    pub(crate) script: Vec<RonAction<C>>,
+    /// The named entry points of the Talk, with the id of the action each one starts from.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub(crate) entry_points: IndexMap<String, ActionId>,
 }
 
 /// A struct that represents an action in a Talk.
@@ -21,22 +32,61 @@ pub(crate) struct RonTalk {
 /// kind of action, the actors involved in the action, any choices that the user can make during
 /// the action, the text of the action, the ID of the next action to perform, whether the action is
 /// the start of the Talk, and any sound effect associated with the action.
-#[derive(Debug, Default, Deserialize, Clone)]
-pub(crate) struct RonAction {
+///
+/// The fields are serialized in this order, skipping the ones with the default value.
+#[derive(Debug, Serialize)]
+pub(crate) struct RonAction<C = Box<dyn Reflect>> {
     /// The ID of the action.
     pub(crate) id: ActionId,
     /// The kind of action.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_talk")]
     pub(crate) action: NodeKind,
     /// The actors involved in the action.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub(crate) actors: Vec<ActorSlug>,
-    /// Any choices that the user can make during the action.
-    pub(crate) choices: Option<Vec<RonChoice>>,
     /// The text of the action.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) text: Option<String>,
+    /// Any choices that the user can make during the action.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) choices: Option<Vec<RonChoice>>,
+    /// The time limit of the choices and the default choice: `(seconds: 5.0, default_choice: 1)`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<ChoiceTimeout>,
+    /// The conditional branches to follow automatically (the first with a condition that holds).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) branches: Option<Vec<RonBranch>>,
     /// The ID of the next action to perform.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) next: Option<ActionId>,
+    /// The delay in seconds to move to the next action automatically.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auto_advance: Option<f32>,
+    /// The custom components of the node, written as reflected values: `{ "my_game::CameraShake": (strength: 2.0) }`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) components: Vec<C>,
+}
+
+impl<C> Default for RonAction<C> {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            action: NodeKind::default(),
+            actors: vec![],
+            text: None,
+            choices: None,
+            timeout: None,
+            branches: None,
+            next: None,
+            auto_advance: None,
+            components: vec![],
+        }
+    }
+}
+
+/// The `Talk` kind is the default, so it is not written.
+fn is_talk(kind: &NodeKind) -> bool {
+    *kind == NodeKind::Talk
 }
 
 impl From<RonAction> for Action {
@@ -45,14 +95,76 @@ impl From<RonAction> for Action {
         if action_kind == NodeKind::Talk && val.choices.is_some() {
             action_kind = NodeKind::Choice;
         }
+        if action_kind == NodeKind::Talk && val.branches.is_some() {
+            action_kind = NodeKind::Branch;
+        }
+        let choices = match action_kind {
+            NodeKind::Branch => val
+                .branches
+                .map_or(vec![], |b| b.into_iter().map(|b| b.into()).collect()),
+            _ => val
+                .choices
+                .map_or(vec![], |c| c.into_iter().map(|c| c.into()).collect()),
+        };
         Action {
             kind: action_kind,
             actors: val.actors,
-            choices: val
-                .choices
-                .map_or(vec![], |c| c.into_iter().map(|c| c.into()).collect()),
+            choices,
             text: val.text.unwrap_or_default(),
             next: val.next,
+            timeout: val.timeout,
+            auto_advance: val.auto_advance,
+            components: ActionComponents(val.components),
+        }
+    }
+}
+
+impl<'a> RonAction<ReflectSerializer<'a>> {
+    /// The action to write in a talk file, with the components serialized with the type registry.
+    pub(crate) fn write(id: ActionId, action: &'a Action, registry: &'a TypeRegistry) -> Self {
+        let choices = action.choices.iter();
+        let (kind, choices, branches) = match action.kind {
+            // the kind is inferred from the choices and the branches when loading
+            NodeKind::Choice => (
+                NodeKind::Talk,
+                Some(choices.map(|c| c.clone().into()).collect()),
+                None,
+            ),
+            NodeKind::Branch => (
+                NodeKind::Talk,
+                None,
+                Some(choices.map(|c| c.clone().into()).collect()),
+            ),
+            _ => (action.kind.clone(), None, None),
+        };
+        RonAction {
+            id,
+            action: kind,
+            actors: action.actors.clone(),
+            text: Some(action.text.clone()).filter(|t| !t.is_empty()),
+            choices,
+            timeout: action.timeout,
+            branches,
+            next: action.next,
+            auto_advance: action.auto_advance,
+            components: (action.components.0.iter())
+                .map(|c| ReflectSerializer::new(&**c, registry))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> RonTalk<ReflectSerializer<'a>> {
+    /// The talk to write in a talk file, with the components serialized with the type registry.
+    pub(crate) fn write(data: &'a TalkData, registry: &'a TypeRegistry) -> Self {
+        RonTalk {
+            actors: (data.actors.iter())
+                .map(|a| RonActor::write(a, data.actor_components.get(&a.slug), registry))
+                .collect(),
+            script: (data.script.iter())
+                .map(|(&id, a)| RonAction::write(id, a, registry))
+                .collect(),
+            entry_points: data.entry_points.clone(),
         }
     }
 }
@@ -60,27 +172,79 @@ impl From<RonAction> for Action {
 /// A struct that represents an actor in a Talk.
 ///
 /// This struct is used to define an actor in a Talk. It contains the ID of the actor, the
-/// name of the character that the actor plays, and an optional asset that represents the actor's
-/// appearance or voice.
-#[derive(Debug, Deserialize, Clone, Default)]
-pub(crate) struct RonActor {
+/// name of the character that the actor plays, the optional portrait and voice assets of the actor,
+/// and the custom components to add to the actor entity.
+#[derive(Debug, Serialize)]
+pub(crate) struct RonActor<C = Box<dyn Reflect>> {
     /// A string identifying uniquely the actor.
     pub(crate) slug: ActorSlug,
     /// The name of the character that the actor plays.
     pub(crate) name: String,
-    // An optional asset that represents the actor's appearance or voice.
-    // pub(crate) asset: Option<String>,
+    /// The path of the portrait image of the actor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) portrait: Option<String>,
+    /// The path of the voice sound of the actor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) voice: Option<String>,
+    /// The custom components of the actor entity, written as reflected values like the components of the actions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) components: Vec<C>,
+}
+
+impl<C> Default for RonActor<C> {
+    fn default() -> Self {
+        Self {
+            slug: ActorSlug::default(),
+            name: String::default(),
+            portrait: None,
+            voice: None,
+            components: vec![],
+        }
+    }
 }
+
+impl<'a> RonActor<ReflectSerializer<'a>> {
+    /// The actor to write in a talk file, with the components serialized with the type registry.
+    pub(crate) fn write(
+        actor: &'a Actor,
+        components: Option<&'a ActionComponents>,
+        registry: &'a TypeRegistry,
+    ) -> Self {
+        RonActor {
+            slug: actor.slug.clone(),
+            name: actor.name.clone(),
+            portrait: actor.portrait.clone(),
+            voice: actor.voice.clone(),
+            components: (components.into_iter().flat_map(|c| c.0.iter()))
+                .map(|c| ReflectSerializer::new(&**c, registry))
+                .collect(),
+        }
+    }
+}
+
 /// A struct that represents a choice in a Talk.
 ///
 /// This struct is used to define a choice in a Talk. It contains the text of the choice and
 /// the ID of the next action to perform if the choice is selected.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub(crate) struct RonChoice {
     /// The text of the choice.
     pub(crate) text: String,
     /// The ID of the next action to perform if the choice is selected.
     pub(crate) next: ActionId,
+    /// The condition that must hold for the choice to be available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) condition: Option<ConditionId>,
+}
+
+impl From<ChoiceData> for RonChoice {
+    fn from(val: ChoiceData) -> Self {
+        RonChoice {
+            text: val.text,
+            next: val.next,
+            condition: val.condition,
+        }
+    }
 }
 
 impl From<RonChoice> for ChoiceData {
@@ -88,6 +252,39 @@ impl From<RonChoice> for ChoiceData {
         ChoiceData {
             text: val.text,
             next: val.next,
+            condition: val.condition,
+        }
+    }
+}
+
+/// A struct that represents a conditional branch in a Talk.
+///
+/// It contains the condition to check and the ID of the next action to perform if the condition holds.
+/// A branch without condition is always followed.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct RonBranch {
+    /// The condition that must hold to follow the branch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) condition: Option<ConditionId>,
+    /// The ID of the next action to perform if the branch is followed.
+    pub(crate) next: ActionId,
+}
+
+impl From<RonBranch> for ChoiceData {
+    fn from(val: RonBranch) -> Self {
+        ChoiceData {
+            text: String::new(),
+            next: val.next,
+            condition: val.condition,
+        }
+    }
+}
+
+impl From<ChoiceData> for RonBranch {
+    fn from(val: ChoiceData) -> Self {
+        RonBranch {
+            condition: val.condition,
+            next: val.next,
         }
     }
 }
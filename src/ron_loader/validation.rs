@@ -0,0 +1,271 @@
+//! The validation shared by the talk loaders (RON, JSON and YAML), that turns the parsed talk into a [`TalkData`].
+
+use bevy::utils::hashbrown::HashSet;
+use indexmap::IndexMap;
+
+use crate::{
+    prelude::{Action, ActionId, Actor, ActorSlug, NodeKind, TalkData},
+    talk_asset::ActionComponents,
+};
+
+use super::{loader::RonLoaderError, types::RonTalk};
+
+/// Builds a `TalkData` from a parsed talk file, checking that it is well formed.
+///
+/// # Errors
+/// If an actor slug or an action id is duplicated,
+/// or if more than one action is a start action, or if an action or an entry point points to a missing action, or an action is performed by an undefined actor,
+/// or if an action has a timeout but it is not a choice with the default choice.
+pub(crate) fn validate_talk(ron_talk: RonTalk) -> Result<TalkData, RonLoaderError> {
+    // 1. Build the actors vec
+    let actors = ron_talk.actors;
+    let mut talk_actors = Vec::<Actor>::with_capacity(actors.len());
+
+    let mut slug_set = HashSet::<ActorSlug>::with_capacity(actors.len());
+    let mut actor_components = IndexMap::new();
+
+    for actor in actors {
+        let slug = actor.slug.clone();
+
+        if !slug_set.insert(slug.clone()) {
+            return Err(RonLoaderError::DuplicateActorSlug(slug));
+        }
+        if !actor.components.is_empty() {
+            actor_components.insert(slug.clone(), ActionComponents(actor.components));
+        }
+        talk_actors.push(Actor {
+            name: actor.name,
+            slug,
+            portrait: actor.portrait,
+            voice: actor.voice,
+        })
+    }
+
+    // 2. build the raw_actions vec
+    let mut raw_actions = IndexMap::<ActionId, Action>::with_capacity(ron_talk.script.len());
+    for action in ron_talk.script {
+        let id = action.id;
+        if raw_actions.insert(id, action.into()).is_some() {
+            return Err(RonLoaderError::DuplicateActionId(id));
+        }
+    }
+
+    validate_start(&raw_actions)?;
+    validate_all_nexts(&raw_actions)?; // check if all nexts point to real actions
+    validate_actors(slug_set, &raw_actions)?;
+    validate_timeouts(&raw_actions)?;
+
+    for (name, id) in &ron_talk.entry_points {
+        if !raw_actions.contains_key(id) {
+            return Err(RonLoaderError::InvalidEntryPoint(name.clone(), *id));
+        }
+    }
+
+    let raw_talk = TalkData {
+        actors: talk_actors,
+        script: raw_actions,
+        entry_points: ron_talk.entry_points,
+        actor_components,
+        actor_assets: vec![],
+    };
+
+    Ok(raw_talk)
+}
+
+/// Check if the actions use only actors that are defined in the talk.
+fn validate_actors(
+    actor_slugs: HashSet<ActorSlug>,
+    actions: &IndexMap<ActionId, Action>,
+) -> Result<(), RonLoaderError> {
+    for action in actions.values() {
+        for slug in action.actors.iter() {
+            if !actor_slugs.contains(slug) {
+                return Err(RonLoaderError::InvalidActorSlug(slug.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that there is at most one start action.
+fn validate_start(actions: &IndexMap<ActionId, Action>) -> Result<(), RonLoaderError> {
+    let mut starts = (actions.iter()).filter(|(_, a)| a.kind == NodeKind::Start);
+    match (starts.next(), starts.next()) {
+        (Some((first, _)), Some((second, _))) => {
+            Err(RonLoaderError::MultipleStartActions(*first, *second))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check if the actions with a timeout are choices and have the default choice.
+fn validate_timeouts(actions: &IndexMap<ActionId, Action>) -> Result<(), RonLoaderError> {
+    for (id, action) in actions {
+        if let Some(timeout) = &action.timeout {
+            if action.kind != NodeKind::Choice || timeout.default_choice >= action.choices.len() {
+                return Err(RonLoaderError::InvalidTimeout(*id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check if all `next` fields and `Choice` `next` fields in a `Vec<RawAction>` point to real actions.
+/// If the action has choices, the `next` field is not checked.
+///
+/// Returns a `TalkError::InvalidNextAction` error if any of the `next` fields or `Choice` `next` fields in the `RawAction`s do not point to real actions.
+fn validate_all_nexts(actions: &IndexMap<ActionId, Action>) -> Result<(), RonLoaderError> {
+    let id_set = actions.keys().cloned().collect::<HashSet<_>>();
+    for (id, action) in actions {
+        if !action.choices.is_empty() {
+            for choice in action.choices.iter() {
+                if !id_set.contains(&choice.next) {
+                    return Err(RonLoaderError::InvalidNextAction(*id, choice.next));
+                }
+            }
+        } else if let Some(next_id) = &action.next {
+            if !id_set.contains(next_id) {
+                return Err(RonLoaderError::InvalidNextAction(*id, *next_id));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+
+    use bevy::prelude::*;
+    use rstest::rstest;
+
+    use crate::{prelude::*, ron_loader::types::RonAction};
+
+    use super::*;
+
+    #[test]
+    fn error_invalid_next_action() {
+        let talk = TalkData {
+            script: indexmap! {0 => Action {
+                next: Some(2),
+                ..default()
+            }},
+            ..default()
+        };
+        let res = validate_all_nexts(&talk.script);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn error_not_found_in_choice() {
+        let talk = TalkData {
+            actors: default(),
+            script: indexmap! {
+                0 => Action {
+                    choices: vec![ChoiceData { next: 2, ..default()}],
+                    ..default()
+                },
+                1 => Action {
+                    ..default()
+                },
+            },
+            ..default()
+        };
+        let res = validate_all_nexts(&talk.script);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn error_duplicate_action_id() {
+        let talk = RonTalk {
+            actors: vec![],
+            script: vec![
+                RonAction { id: 1, ..default() },
+                RonAction { id: 1, ..default() },
+            ],
+            entry_points: default(),
+        };
+        let res = validate_talk(talk);
+        assert!(matches!(res, Err(RonLoaderError::DuplicateActionId(1))));
+    }
+
+    #[test]
+    fn error_multiple_start_actions() {
+        let start = |id| RonAction {
+            id,
+            action: NodeKind::Start,
+            next: Some(2),
+            ..default()
+        };
+        let talk = RonTalk {
+            actors: vec![],
+            script: vec![start(1), RonAction { id: 2, ..default() }, start(3)],
+            entry_points: default(),
+        };
+        let res = validate_talk(talk);
+        assert!(matches!(
+            res,
+            Err(RonLoaderError::MultipleStartActions(1, 3))
+        ));
+    }
+
+    #[test]
+    fn error_invalid_entry_point() {
+        let talk = RonTalk {
+            actors: vec![],
+            script: vec![RonAction { id: 1, ..default() }],
+            entry_points: indexmap! {"after_quest".to_string() => 2},
+        };
+        let res = validate_talk(talk);
+        assert!(
+            matches!(res, Err(RonLoaderError::InvalidEntryPoint(name, 2)) if name == "after_quest")
+        );
+    }
+
+    #[rstest]
+    #[case(Action { timeout: Some(ChoiceTimeout { seconds: 1.0, default_choice: 0 }), ..default() })]
+    #[case(Action {
+        kind: NodeKind::Choice,
+        choices: vec![ChoiceData { next: 1, ..default() }],
+        timeout: Some(ChoiceTimeout { seconds: 1.0, default_choice: 1 }),
+        ..default()
+    })]
+    fn error_invalid_timeout(#[case] action: Action) {
+        let res = validate_timeouts(&indexmap! { 0 => action });
+        assert!(matches!(res, Err(RonLoaderError::InvalidTimeout(0))));
+    }
+
+    #[test]
+    fn test_validate_actors_valid() {
+        let mut actor_slugs = HashSet::<ActorSlug>::new();
+        actor_slugs.insert("actor1".to_string());
+        actor_slugs.insert("actor2".to_string());
+
+        let actions = indexmap! {
+            0 => Action {
+                actors: vec!["actor1".to_string()],
+                ..default()
+            },
+            1 => Action {
+                actors: vec!["actor1".to_string(), "actor2".to_string()],
+                ..default()
+            },
+        };
+        assert!(validate_actors(actor_slugs, &actions).is_ok());
+    }
+
+    #[test]
+    fn test_validate_actors_invalid() {
+        let mut actor_slugs = HashSet::<ActorSlug>::new();
+        actor_slugs.insert("actor1".to_string());
+        actor_slugs.insert("actor2".to_string());
+        let actions = indexmap! {
+            0 => Action {
+                actors: vec!["actor3".to_string()],
+                ..default()
+            },
+        };
+        let result = validate_actors(actor_slugs, &actions);
+        assert!(result.is_err());
+    }
+}
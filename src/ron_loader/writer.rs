@@ -0,0 +1,419 @@
+//! The ron writer, to save talks in the "talk.ron" format.
+
+use std::collections::VecDeque;
+
+use aery::{prelude::*, tuple_traits::RelationEntries};
+use bevy::{
+    ecs::system::Command,
+    prelude::*,
+    reflect::TypeRegistry,
+    utils::{HashMap, HashSet},
+};
+use indexmap::IndexMap;
+use serde_ron::ser::{to_string_pretty, PrettyConfig};
+
+use crate::prelude::{
+    Action, ActionId, Actor, AutoAdvance, BranchNode, BuildTalkCommand, ChoiceData, ChoiceNode,
+    ChoiceTimeout, EntryPoint, FollowedBy, IncrementNode, JoinNode, LeaveNode, NodeId, NodeKind,
+    PerformedBy, SetNode, StartNode, Talk, TalkBuilder, TalkData, TextNode,
+};
+
+use super::types::RonTalk;
+
+impl TalkData {
+    /// Serializes the talk in the canonical talk.ron format.
+    ///
+    /// The actions are written in the order of the script, without the fields with default values.
+    /// Loading the output gives back the same `TalkData`, so loading and saving a canonical file gives the same file.
+    ///
+    /// The custom components of the actions are serialized with the type registry (usually the `AppTypeRegistry`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_talks::prelude::*;
+    ///
+    /// fn save(talks: Res<Assets<TalkData>>, registry: Res<AppTypeRegistry>) {
+    ///     for (_, talk) in talks.iter() {
+    ///         let ron = talk.to_ron(&registry.read()).unwrap();
+    ///         // std::fs::write("assets/saved.talk.ron", ron);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// If the data cannot be serialized.
+    pub fn to_ron(&self, registry: &TypeRegistry) -> Result<String, serde_ron::Error> {
+        let config = PrettyConfig::new()
+            .indentor("  ".to_string())
+            .struct_names(false)
+            .separate_tuple_members(false)
+            .enumerate_arrays(false);
+        to_string_pretty(&RonTalk::write(self, registry), config)
+    }
+
+    /// Extracts the data of a spawned talk graph. Returns `None` if the entity is not a talk.
+    ///
+    /// The nodes keep their [`NodeId`] as action id if all the ids are numbers
+    /// (like in the talks loaded from talk.ron files), otherwise they are numbered from 1 following the graph.
+    /// The first action is the one after the start node, then the others are sorted by id.
+    /// Custom components and actors that don't perform in any node are not part of the data.
+    pub fn from_talk(world: &mut World, talk: Entity) -> Option<Self> {
+        world.get::<Talk>(talk)?;
+        let nodes = world.get::<Children>(talk).map_or(vec![], |c| c.to_vec());
+        let mut starts = world.query_filtered::<Option<&EntryPoint>, With<StartNode>>();
+        let starts = nodes
+            .into_iter()
+            .filter_map(|n| Some((n, starts.get(world, n).ok()?.map(|e| e.0.clone()))))
+            .collect::<Vec<_>>();
+        let Some(&(start, _)) = starts.iter().find(|(_, entry)| entry.is_none()) else {
+            return Some(Self::default());
+        };
+
+        let mut edges = world.query::<Relations<FollowedBy>>();
+        let mut followers = |world: &World, node| {
+            edges
+                .get(world, node)
+                .map(|f| f.targets(FollowedBy).to_vec())
+                .unwrap_or_default()
+        };
+
+        // visit the graph from the start node, then from the entry points
+        let mut order = vec![];
+        let mut visited = starts.iter().map(|(n, _)| *n).collect::<HashSet<_>>();
+        let mut queue = VecDeque::from(followers(world, start));
+        let mut entry_nodes = vec![];
+        for (node, entry) in &starts {
+            if let Some(name) = entry {
+                let first = followers(world, *node);
+                entry_nodes.extend(first.first().map(|&f| (name.clone(), f)));
+                queue.extend(first);
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            if visited.insert(node) {
+                order.push(node);
+                queue.extend(followers(world, node));
+            }
+        }
+
+        let numeric_ids = order
+            .iter()
+            .map(|&n| world.get::<NodeId>(n)?.0.parse::<ActionId>().ok())
+            .collect::<Option<Vec<_>>>()
+            .filter(|ids| ids.iter().collect::<HashSet<_>>().len() == ids.len());
+        let ids = match numeric_ids {
+            Some(ids) => order.iter().copied().zip(ids).collect::<HashMap<_, _>>(),
+            None => order.iter().copied().zip(1..).collect(),
+        };
+
+        let mut performers_query = world.query::<Relations<PerformedBy>>();
+        let mut actors: Vec<Actor> = vec![];
+        let mut script = IndexMap::with_capacity(order.len());
+        for &node in &order {
+            let performers = performers_query
+                .get(world, node)
+                .map_or(vec![], |p| p.targets(PerformedBy).to_vec())
+                .into_iter()
+                .filter_map(|a| world.get::<Actor>(a))
+                .collect::<Vec<_>>();
+            for actor in &performers {
+                if !actors.iter().any(|a| a.slug == actor.slug) {
+                    actors.push((*actor).clone());
+                }
+            }
+
+            let node = world.entity(node);
+            let mut action = Action {
+                actors: performers.iter().map(|a| a.slug.clone()).collect(),
+                text: node
+                    .get::<TextNode>()
+                    .map(|t| t.0.clone())
+                    .unwrap_or_default(),
+                next: followers(world, node.id()).first().map(|f| ids[f]),
+                timeout: node.get::<ChoiceTimeout>().copied(),
+                auto_advance: node.get::<AutoAdvance>().map(|a| a.0),
+                ..default()
+            };
+            action.kind = if let Some(ChoiceNode(choices)) = node.get() {
+                action.next = None;
+                action.choices = choices
+                    .iter()
+                    .map(|c| ChoiceData {
+                        text: c.text.clone(),
+                        next: ids[&c.next],
+                        condition: c.condition.clone(),
+                    })
+                    .collect();
+                NodeKind::Choice
+            } else if let Some(BranchNode(branches)) = node.get() {
+                action.next = None;
+                action.choices = branches
+                    .iter()
+                    .map(|b| ChoiceData {
+                        next: ids[&b.next],
+                        condition: b.condition.clone(),
+                        ..default()
+                    })
+                    .collect();
+                NodeKind::Branch
+            } else if let Some(SetNode { name, value }) = node.get() {
+                NodeKind::Set(name.clone(), value.clone())
+            } else if let Some(IncrementNode { name, amount }) = node.get() {
+                NodeKind::Increment(name.clone(), amount.clone())
+            } else if node.contains::<JoinNode>() {
+                NodeKind::Join
+            } else if node.contains::<LeaveNode>() {
+                NodeKind::Leave
+            } else if node.contains::<TextNode>() {
+                NodeKind::Talk
+            } else {
+                NodeKind::Empty
+            };
+            script.insert(ids[&node.id()], action);
+        }
+        // the first action is the start of the talk
+        if let Some(&first) = order.first().map(|n| &ids[n]) {
+            script.sort_by(|a, _, b, _| (*a != first, a).cmp(&(*b != first, b)));
+        }
+
+        let mut data = Self::new(script, actors);
+        data.entry_points = (entry_nodes.into_iter())
+            .map(|(name, node)| (name, ids[&node]))
+            .collect();
+        Some(data)
+    }
+
+    /// Builds the talk of the builder in a scratch world and extracts its data.
+    /// The custom components of the nodes are dropped. See [`TalkData::from_talk`].
+    pub fn from_builder(mut builder: TalkBuilder) -> Self {
+        builder.retain_components(&|c| {
+            c.is::<TextNode>()
+                || c.is::<JoinNode>()
+                || c.is::<LeaveNode>()
+                || c.is::<SetNode>()
+                || c.is::<IncrementNode>()
+                || c.is::<AutoAdvance>()
+                || c.is::<ChoiceTimeout>()
+        });
+
+        let mut world = World::new();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<TextNode>();
+            registry.register::<JoinNode>();
+            registry.register::<LeaveNode>();
+            registry.register::<SetNode>();
+            registry.register::<IncrementNode>();
+            registry.register::<AutoAdvance>();
+            registry.register::<ChoiceTimeout>();
+        }
+        world.insert_resource(registry);
+
+        let talk = world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder).apply(&mut world);
+        Self::from_talk(&mut world, talk).expect("built talk")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{
+        prelude::*,
+        ron_loader::loader::parse_ron,
+        tests::{spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    const CANONICAL: &str = r#"(
+  actors: [
+    (
+      slug: "ferris",
+      name: "Ferris",
+    ),
+  ],
+  script: [
+    (
+      id: 1,
+      actors: [
+        "ferris",
+      ],
+      text: Some("Hello {name}"),
+      next: Some(2),
+      auto_advance: Some(1.5),
+    ),
+    (
+      id: 2,
+      action: Set("gold", 5),
+      next: Some(3),
+    ),
+    (
+      id: 3,
+      choices: Some([
+        (
+          text: "Buy",
+          next: 4,
+          condition: Some("rich"),
+        ),
+        (
+          text: "Leave",
+          next: 5,
+        ),
+      ]),
+      timeout: Some((
+        seconds: 5.0,
+        default_choice: 1,
+      )),
+    ),
+    (
+      id: 4,
+      branches: Some([
+        (
+          condition: Some("met_ferris"),
+          next: 5,
+        ),
+        (
+          next: 6,
+        ),
+      ]),
+    ),
+    (
+      id: 5,
+      action: Join,
+      actors: [
+        "ferris",
+      ],
+      next: Some(6),
+    ),
+    (
+      id: 6,
+      action: Empty,
+    ),
+  ],
+)"#;
+
+    #[test]
+    fn load_and_save_gives_the_same_file() {
+        let registry = TypeRegistry::default();
+        let data = parse_ron(CANONICAL.as_bytes(), &registry).unwrap();
+        assert_eq!(data.to_ron(&registry).unwrap(), CANONICAL);
+    }
+
+    #[rstest]
+    #[case(include_str!("../../assets/talks/simple.talk.ron"))]
+    #[case(include_str!("../../assets/talks/choices.talk.ron"))]
+    #[case(include_str!("../../assets/talks/full.talk.ron"))]
+    #[case(include_str!("../../assets/talks/interact_a.talk.ron"))]
+    #[case(include_str!("../../assets/talks/interact_b.talk.ron"))]
+    fn round_trip_of_the_assets(#[case] file: &str) {
+        let registry = TypeRegistry::default();
+        let data = parse_ron(file.as_bytes(), &registry).unwrap();
+        let saved = data.to_ron(&registry).unwrap();
+        let loaded = parse_ron(saved.as_bytes(), &registry).unwrap();
+        assert_eq!(loaded, data);
+        assert_eq!(loaded.to_ron(&registry).unwrap(), saved);
+    }
+
+    #[rstest]
+    #[case(CANONICAL)]
+    #[case(include_str!("../../assets/talks/full.talk.ron"))]
+    fn extract_from_a_spawned_talk(#[case] file: &str) {
+        let registry = TypeRegistry::default();
+        let data = parse_ron(file.as_bytes(), &registry).unwrap();
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+
+        let extracted = TalkData::from_talk(&mut app.world, talk).unwrap();
+        // same actions, but sorted by id after the first one
+        assert_eq!(extracted.script, data.script);
+        assert_eq!(extracted.script.first(), data.script.first());
+        assert!(extracted.script.keys().skip(1).is_sorted());
+        for actor in &extracted.actors {
+            assert!(data.actors.contains(actor));
+        }
+    }
+
+    const ENTRY_POINTS: &str = r#"(
+  actors: [],
+  script: [
+    (
+      id: 1,
+      text: Some("Nice to meet you!"),
+      next: Some(2),
+    ),
+    (
+      id: 2,
+      text: Some("What can I do for you?"),
+    ),
+    (
+      id: 3,
+      text: Some("Thanks for the help!"),
+      next: Some(2),
+    ),
+  ],
+  entry_points: {
+    "after_quest": 3,
+    "again": 2,
+  },
+)"#;
+
+    #[test]
+    fn entry_points_are_loaded_saved_and_extracted() {
+        let registry = TypeRegistry::default();
+        let data = parse_ron(ENTRY_POINTS.as_bytes(), &registry).unwrap();
+        assert_eq!(data.entry_points["after_quest"], 3);
+        assert_eq!(data.to_ron(&registry).unwrap(), ENTRY_POINTS);
+
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+        assert_eq!(TalkData::from_talk(&mut app.world, talk).unwrap(), data);
+    }
+
+    #[test]
+    fn extract_from_a_builder() {
+        let registry = TypeRegistry::default();
+        #[derive(Component, Reflect, Default)]
+        #[reflect(Component)]
+        struct Custom;
+
+        let builder = TalkBuilder::default()
+            .add_actor(Actor::new("ferris", "Ferris"))
+            .actor_say("ferris", "Hello")
+            .with_component(Custom)
+            .choose(vec![
+                ("Again", TalkBuilder::default().set("again", true)),
+                ("Bye", TalkBuilder::default().say("Bye")),
+            ]);
+
+        let data = TalkData::from_builder(builder);
+        assert_eq!(data.actors, vec![Actor::new("ferris", "Ferris")]);
+        assert_eq!(
+            data.script.keys().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(data.script[&1].text, "Hello");
+        assert_eq!(data.script[&1].next, Some(2));
+        assert_eq!(data.script[&2].kind, NodeKind::Choice);
+        assert_eq!(data.script[&2].choices[1].text, "Bye");
+        assert_eq!(
+            data.script[&3].kind,
+            NodeKind::Set("again".to_string(), TalkValue::Bool(true))
+        );
+        assert_eq!(data.script[&4].text, "Bye");
+
+        // the written file can be loaded back
+        let loaded = parse_ron(data.to_ron(&registry).unwrap().as_bytes(), &registry).unwrap();
+        assert_eq!(loaded, data);
+    }
+
+    #[test]
+    fn not_a_talk() {
+        let mut app = talks_minimal_app();
+        let e = app.world.spawn_empty().id();
+        assert_eq!(TalkData::from_talk(&mut app.world, e), None);
+    }
+}
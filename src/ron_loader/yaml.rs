@@ -0,0 +1,120 @@
+//! The yaml Asset Loader, with the same schema as the talk.ron files.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::{AppTypeRegistry, FromWorld, World},
+    reflect::{TypeRegistry, TypeRegistryArc},
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeSeed;
+
+use crate::prelude::{TalkData, TalksLoaderSettings};
+
+use super::loader::{load_with, parse_with, RonLoaderError};
+
+/// Load Talks from yaml assets (`.talk.yaml` files).
+pub struct YamlTalksLoader {
+    /// The type registry of the app.
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for YamlTalksLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+        }
+    }
+}
+
+impl AssetLoader for YamlTalksLoader {
+    type Asset = TalkData;
+    type Settings = TalksLoaderSettings;
+    type Error = RonLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(load_with(reader, settings, load_context, |bytes| {
+            parse_yaml(bytes, &self.type_registry.read())
+        }))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["talk.yaml"]
+    }
+}
+
+/// Parses and validates the content of a talk.yaml file.
+pub(crate) fn parse_yaml(
+    bytes: &[u8],
+    registry: &TypeRegistry,
+) -> Result<TalkData, RonLoaderError> {
+    parse_with(bytes, registry, |bytes, seed| {
+        seed.deserialize(serde_yaml::Deserializer::from_slice(bytes))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ron_loader::loader::parse_ron;
+
+    use super::*;
+
+    #[test]
+    fn parse_the_same_schema_as_ron() {
+        let registry = TypeRegistry::default();
+        let yaml = r#"
+actors:
+  - slug: ferris
+    name: Ferris
+script:
+  - id: 1
+    actors: [ferris]
+    text: Hello
+    next: 2
+  - id: 2
+    action: !Increment [gold, -5]
+    next: 3
+  - id: 3
+    choices:
+      - text: Buy
+        next: 4
+        condition: rich
+      - text: Leave
+        next: 4
+  - id: 4
+    action: Join
+    actors: [ferris]
+"#;
+        let ron = r#"(
+            actors: [(slug: "ferris", name: "Ferris")],
+            script: [
+                (id: 1, actors: ["ferris"], text: Some("Hello"), next: Some(2)),
+                (id: 2, action: Increment("gold", -5), next: Some(3)),
+                (id: 3, choices: Some([
+                    (text: "Buy", next: 4, condition: Some("rich")),
+                    (text: "Leave", next: 4),
+                ])),
+                (id: 4, action: Join, actors: ["ferris"]),
+            ],
+        )"#;
+        assert_eq!(
+            parse_yaml(yaml.as_bytes(), &registry).unwrap(),
+            parse_ron(ron.as_bytes(), &registry).unwrap()
+        );
+    }
+
+    #[test]
+    fn validate_like_ron() {
+        let registry = TypeRegistry::default();
+        let yaml = "actors: []\nscript:\n  - id: 1\n    next: 2\n";
+        let err = parse_yaml(yaml.as_bytes(), &registry).unwrap_err();
+        assert!(matches!(err, RonLoaderError::InvalidNextAction(1, 2)));
+
+        let err = parse_yaml(b"actors: [", &registry).unwrap_err();
+        assert!(matches!(err, RonLoaderError::YamlError(_)));
+    }
+}
@@ -0,0 +1,760 @@
+//! The Yarn Spinner Asset Loader.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    utils::{BoxedFuture, HashMap},
+};
+use thiserror::Error;
+
+use crate::{
+    conditions::ConditionId,
+    prelude::{Action, ActionId, Actor, ActorSlug, ChoiceData, NodeKind, TalkData},
+    variables::{TalkValue, VariableName},
+};
+
+use super::script::ScriptBuilder;
+
+/// Load Talks from Yarn Spinner scripts (`.yarn` files).
+///
+/// Only the subset of the language that maps to the talk graphs is supported:
+/// - the nodes with their `title` header (the talk starts from the `Start` node, or the first one if missing)
+/// - lines, with an optional speaker prefix (`Ferris: Hello`) that becomes an actor
+/// - options (`->`) with their indented bodies and an optional `<<if $var>>` condition
+/// - `<<jump Node>>` and `<<stop>>`
+/// - `<<set $var to value>>` with literal values, and `<<set $var to $var + 1>>` increments
+/// - `<<if>>`, `<<elseif>>`, `<<else>>` and `<<endif>>` with a variable or a named condition
+///
+/// The `{$var}` placeholders become `{var}` placeholders. Everything else is a [`YarnLoaderError`].
+pub struct YarnLoader;
+
+/// The error type for the Yarn Talks loader.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum YarnLoaderError {
+    /// An [IO Error](std::io::Error)
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A node without the `title` header
+    #[error("line {0}: the node has no title")]
+    MissingTitle(usize),
+    /// Multiple nodes have the same title
+    #[error("line {0}: the node title {1} is duplicated")]
+    DuplicateNode(usize, String),
+    /// A node without the closing `===`
+    #[error("line {0}: the node is not closed with ===")]
+    UnclosedNode(usize),
+    /// A line that is not valid in its position
+    #[error("line {0}: unexpected `{1}`")]
+    UnexpectedLine(usize, String),
+    /// A command that has no equivalent in the talks
+    #[error("line {0}: the command <<{1}>> is not supported")]
+    UnsupportedCommand(usize, String),
+    /// An expression that is not a literal, a variable or a condition id
+    #[error("line {0}: the expression `{1}` is not supported, only literals, variables and condition ids")]
+    UnsupportedExpression(usize, String),
+    /// An `<<if>>` without the `<<endif>>`
+    #[error("line {0}: the <<if>> is not closed with <<endif>>")]
+    UnclosedIf(usize),
+    /// A jump to a node that is not in the file
+    #[error("line {0}: jump to the unknown node {1}")]
+    UnknownNode(usize, String),
+    /// Nodes that only jump to each other
+    #[error("the node {0} only jumps to other nodes endlessly")]
+    EndlessJump(String),
+}
+
+impl AssetLoader for YarnLoader {
+    type Asset = TalkData;
+    type Settings = ();
+    type Error = YarnLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut source = String::new();
+            reader.read_to_string(&mut source).await?;
+            parse_yarn(&source)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yarn"]
+    }
+}
+
+/// Parses a Yarn Spinner script into a [`TalkData`].
+pub(crate) fn parse_yarn(source: &str) -> Result<TalkData, YarnLoaderError> {
+    let nodes = parse_nodes(source)?;
+    Compiler::default().compile(&nodes)
+}
+
+/// A line of the body of a node.
+struct Line<'a> {
+    /// The line number in the file, starting from 1.
+    number: usize,
+    /// The number of leading whitespaces.
+    indent: usize,
+    /// The trimmed content, without hashtags.
+    text: &'a str,
+}
+
+/// A node of the script, with its parsed body.
+struct YarnNode {
+    /// The line of the node header.
+    line: usize,
+    /// The title of the node.
+    title: String,
+    /// The statements of the body.
+    body: Vec<Statement>,
+}
+
+/// An option of an options group.
+struct YarnOption {
+    /// The text of the option.
+    text: String,
+    /// The condition of the option.
+    condition: Option<ConditionId>,
+    /// The statements that follow if the option is chosen.
+    body: Vec<Statement>,
+}
+
+/// The supported statements of a node body.
+enum Statement {
+    /// A line, with the optional speaker.
+    Say(Option<String>, String),
+    /// A group of options.
+    Options(Vec<YarnOption>),
+    /// The clauses of an `<<if>>`. The `<<else>>` clause has no condition.
+    If(Vec<(Option<ConditionId>, Vec<Statement>)>),
+    /// A `<<set>>` with a literal value.
+    Set(VariableName, TalkValue),
+    /// A `<<set>>` that adds an amount to the variable itself.
+    Increment(VariableName, TalkValue),
+    /// A `<<jump>>` to the node with the title, at the given line.
+    Jump(usize, String),
+    /// A `<<stop>>`.
+    Stop,
+}
+
+/// Splits the source in nodes and parses their bodies.
+fn parse_nodes(source: &str) -> Result<Vec<YarnNode>, YarnLoaderError> {
+    let mut nodes = vec![];
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l))
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim().starts_with("//"));
+
+    while let Some((header_line, first)) = lines.next() {
+        // the headers
+        let mut title = None;
+        let mut header = Some(first);
+        while let Some(text) = header.map(str::trim) {
+            if text == "---" {
+                break;
+            }
+            match text.split_once(':') {
+                Some((key, value)) if key.trim() == "title" => title = Some(value.trim()),
+                Some(_) => (),
+                None => return Err(YarnLoaderError::UnexpectedLine(header_line, text.into())),
+            }
+            header = lines.next().map(|(_, l)| l);
+        }
+        if header.is_none() {
+            return Err(YarnLoaderError::UnclosedNode(header_line));
+        }
+        let title = title.ok_or(YarnLoaderError::MissingTitle(header_line))?;
+
+        // the body
+        let mut body = vec![];
+        loop {
+            let (number, raw) = lines
+                .next()
+                .ok_or(YarnLoaderError::UnclosedNode(header_line))?;
+            let text = raw.trim();
+            if text == "===" {
+                break;
+            }
+            body.push(Line {
+                number,
+                indent: raw.len() - raw.trim_start().len(),
+                text: strip_hashtags(text),
+            });
+        }
+
+        nodes.push(YarnNode {
+            line: header_line,
+            title: title.to_string(),
+            body: Parser {
+                lines: &body,
+                pos: 0,
+            }
+            .block(0, false)?,
+        });
+    }
+    Ok(nodes)
+}
+
+/// The parser of the statements of a node body.
+struct Parser<'a> {
+    /// The lines of the body.
+    lines: &'a [Line<'a>],
+    /// The index of the next line to parse.
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Parses the statements until a line less indented than `indent` or,
+    /// if inside an `<<if>>`, one of its clauses.
+    fn block(&mut self, indent: usize, in_if: bool) -> Result<Vec<Statement>, YarnLoaderError> {
+        let mut statements = vec![];
+        while let Some(line) = self.lines.get(self.pos) {
+            let is_clause = matches!(command(line.text), Some(("elseif" | "else" | "endif", _)));
+            if line.indent < indent || (in_if && is_clause) {
+                break;
+            }
+            statements.push(self.statement(indent, in_if)?);
+        }
+        Ok(statements)
+    }
+
+    /// Parses the statement starting at the current line.
+    fn statement(&mut self, indent: usize, in_if: bool) -> Result<Statement, YarnLoaderError> {
+        let line = &self.lines[self.pos];
+        if line.text.starts_with("->") {
+            return self.options(in_if);
+        }
+        self.pos += 1;
+
+        let Some((name, args)) = command(line.text) else {
+            if line.text.contains("<<") {
+                return Err(YarnLoaderError::UnexpectedLine(
+                    line.number,
+                    line.text.into(),
+                ));
+            }
+            let text = placeholders(line.text);
+            return Ok(match text.split_once(':') {
+                Some((speaker, text)) if is_speaker(speaker) => {
+                    Statement::Say(Some(speaker.trim().to_string()), text.trim().to_string())
+                }
+                _ => Statement::Say(None, text),
+            });
+        };
+
+        match name {
+            "jump" if is_identifier(args) => Ok(Statement::Jump(line.number, args.to_string())),
+            "jump" => Err(YarnLoaderError::UnsupportedExpression(
+                line.number,
+                args.into(),
+            )),
+            "stop" => Ok(Statement::Stop),
+            "set" => set(line.number, args),
+            "if" => {
+                let mut clauses = vec![(
+                    Some(condition(line.number, args)?),
+                    self.block(indent, true)?,
+                )];
+                loop {
+                    let clause = self.lines.get(self.pos).and_then(|l| command(l.text));
+                    let clause_line = self.lines.get(self.pos).map_or(0, |l| l.number);
+                    self.pos += 1;
+                    match clause {
+                        Some(("elseif", args)) => clauses.push((
+                            Some(condition(clause_line, args)?),
+                            self.block(indent, true)?,
+                        )),
+                        Some(("else", _)) => clauses.push((None, self.block(indent, true)?)),
+                        Some(("endif", _)) => return Ok(Statement::If(clauses)),
+                        _ => return Err(YarnLoaderError::UnclosedIf(line.number)),
+                    }
+                }
+            }
+            "elseif" | "else" | "endif" => Err(YarnLoaderError::UnexpectedLine(
+                line.number,
+                line.text.into(),
+            )),
+            _ => Err(YarnLoaderError::UnsupportedCommand(
+                line.number,
+                name.into(),
+            )),
+        }
+    }
+
+    /// Parses a group of options, the consecutive `->` lines with the same indentation.
+    fn options(&mut self, in_if: bool) -> Result<Statement, YarnLoaderError> {
+        let indent = self.lines[self.pos].indent;
+        let mut options = vec![];
+        while let Some(line) = self.lines.get(self.pos) {
+            let Some(text) = line
+                .text
+                .strip_prefix("->")
+                .filter(|_| line.indent == indent)
+            else {
+                break;
+            };
+            self.pos += 1;
+
+            let (text, condition) = match text.find("<<") {
+                None => (text, None),
+                Some(i) => match command(&text[i..]) {
+                    Some(("if", args)) => (&text[..i], Some(condition(line.number, args)?)),
+                    Some((name, _)) => {
+                        return Err(YarnLoaderError::UnsupportedCommand(
+                            line.number,
+                            name.into(),
+                        ))
+                    }
+                    None => {
+                        return Err(YarnLoaderError::UnexpectedLine(
+                            line.number,
+                            line.text.into(),
+                        ))
+                    }
+                },
+            };
+            options.push(YarnOption {
+                text: placeholders(text.trim()),
+                condition,
+                body: self.block(indent + 1, in_if)?,
+            });
+        }
+        Ok(Statement::Options(options))
+    }
+}
+
+/// Splits a `<<command args>>` line in the command name and its trimmed arguments.
+fn command(text: &str) -> Option<(&str, &str)> {
+    let inner = text.strip_prefix("<<")?.strip_suffix(">>")?.trim();
+    Some(
+        inner
+            .split_once(char::is_whitespace)
+            .map_or((inner, ""), |(n, a)| (n, a.trim())),
+    )
+}
+
+/// Parses the arguments of a `<<set>>` command.
+fn set(line: usize, args: &str) -> Result<Statement, YarnLoaderError> {
+    let unsupported = || YarnLoaderError::UnsupportedExpression(line, args.to_string());
+    let (name, value) = args
+        .split_once(" to ")
+        .or_else(|| args.split_once('='))
+        .ok_or_else(unsupported)?;
+    let name = variable(name).ok_or_else(unsupported)?;
+    let value = value.trim();
+
+    if let Some(value) = literal(value) {
+        return Ok(Statement::Set(name.to_string(), value));
+    }
+    // `$var + amount` and `$var - amount` are increments
+    for (op, sign) in [('+', 1), ('-', -1)] {
+        let Some((var, amount)) = value.split_once(op) else {
+            continue;
+        };
+        if variable(var) != Some(name) {
+            continue;
+        }
+        let amount = match literal(amount.trim()) {
+            Some(TalkValue::Int(i)) => TalkValue::Int(sign * i),
+            Some(TalkValue::Float(f)) => TalkValue::Float(sign as f64 * f),
+            _ => continue,
+        };
+        return Ok(Statement::Increment(name.to_string(), amount));
+    }
+    Err(unsupported())
+}
+
+/// Parses the condition of an `<<if>>`: a variable (`$has_key`) or a named condition (`has_key`).
+fn condition(line: usize, expr: &str) -> Result<ConditionId, YarnLoaderError> {
+    let id = expr.strip_prefix('$').unwrap_or(expr);
+    match is_identifier(id) {
+        true => Ok(id.to_string()),
+        false => Err(YarnLoaderError::UnsupportedExpression(
+            line,
+            expr.to_string(),
+        )),
+    }
+}
+
+/// The name of a `$var` expression.
+fn variable(expr: &str) -> Option<&str> {
+    expr.trim().strip_prefix('$').filter(|n| is_identifier(n))
+}
+
+/// Parses a literal value: a boolean, a number or a quoted string.
+fn literal(value: &str) -> Option<TalkValue> {
+    match value {
+        "true" => Some(TalkValue::Bool(true)),
+        "false" => Some(TalkValue::Bool(false)),
+        _ => value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .map(|s| TalkValue::String(s.to_string()))
+            .or_else(|| value.parse().ok().map(TalkValue::Int))
+            .or_else(|| {
+                value
+                    .parse()
+                    .ok()
+                    .filter(|f: &f64| f.is_finite())
+                    .map(TalkValue::Float)
+            }),
+    }
+}
+
+/// Checks if the text is a valid node title, variable name or condition id.
+/// Dots are allowed for the `global.` variables.
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Checks if the text before a colon is a speaker name.
+pub(super) fn is_speaker(text: &str) -> bool {
+    let text = text.trim();
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '_' || c == '-')
+}
+
+/// The actor slug of a speaker name: lowercase, with underscores instead of spaces.
+pub(super) fn slug(speaker: &str) -> ActorSlug {
+    speaker.to_lowercase().replace(' ', "_")
+}
+
+/// Converts the Yarn `{$var}` placeholders into `{var}` placeholders.
+fn placeholders(text: &str) -> String {
+    text.replace("{$", "{")
+}
+
+/// Removes the trailing hashtags (`#line:1234`) of a line.
+fn strip_hashtags(text: &str) -> &str {
+    match text.find(" #") {
+        Some(i) => text[..i].trim_end(),
+        None => text,
+    }
+}
+
+/// Turns the parsed nodes into the actions of a [`TalkData`].
+#[derive(Default)]
+struct Compiler {
+    /// The script being built.
+    script: ScriptBuilder,
+    /// The speakers of the lines.
+    actors: Vec<Actor>,
+    /// The ids reserved for the nodes (the targets of the jumps), by title.
+    node_ids: HashMap<String, ActionId>,
+}
+
+impl Compiler {
+    /// Compiles the nodes. The `Start` node (or the first one) is the start of the talk.
+    fn compile(mut self, nodes: &[YarnNode]) -> Result<TalkData, YarnLoaderError> {
+        if nodes.is_empty() {
+            return Ok(TalkData::default());
+        }
+        for node in nodes {
+            self.add_actors(&node.body);
+            let id = self.script.reserve();
+            if self.node_ids.insert(node.title.clone(), id).is_some() {
+                return Err(YarnLoaderError::DuplicateNode(
+                    node.line,
+                    node.title.clone(),
+                ));
+            }
+        }
+
+        for node in nodes {
+            let entry = self.block(&node.body, None)?;
+            self.script.define(self.node_ids[&node.title], entry);
+        }
+
+        let start = nodes
+            .iter()
+            .find(|n| n.title == "Start")
+            .unwrap_or(&nodes[0]);
+        let script = self
+            .script
+            .finish(Some(self.node_ids[&start.title]))
+            .map_err(|id| {
+                let node = nodes.iter().find(|n| self.node_ids[&n.title] == id);
+                YarnLoaderError::EndlessJump(node.map(|n| n.title.clone()).unwrap_or_default())
+            })?;
+
+        Ok(TalkData::new(script, self.actors))
+    }
+
+    /// Compiles the statements, from the last to the first, and returns the id of the first action.
+    /// `follow` is the action that comes after the block.
+    fn block(
+        &mut self,
+        statements: &[Statement],
+        follow: Option<ActionId>,
+    ) -> Result<Option<ActionId>, YarnLoaderError> {
+        let mut next = follow;
+        for statement in statements.iter().rev() {
+            next = match statement {
+                Statement::Say(speaker, text) => self.script.add(Action {
+                    actors: speaker.iter().map(|s| slug(s)).collect(),
+                    text: text.clone(),
+                    next,
+                    ..Default::default()
+                }),
+                Statement::Set(name, value) => self.script.add(Action {
+                    kind: NodeKind::Set(name.clone(), value.clone()),
+                    next,
+                    ..Default::default()
+                }),
+                Statement::Increment(name, amount) => self.script.add(Action {
+                    kind: NodeKind::Increment(name.clone(), amount.clone()),
+                    next,
+                    ..Default::default()
+                }),
+                Statement::Jump(line, title) => Some(
+                    *self
+                        .node_ids
+                        .get(title)
+                        .ok_or_else(|| YarnLoaderError::UnknownNode(*line, title.clone()))?,
+                ),
+                Statement::Stop => None,
+                Statement::Options(options) => {
+                    let mut choices = Vec::with_capacity(options.len());
+                    for option in options {
+                        let entry = self.block(&option.body, next)?;
+                        choices.push(ChoiceData {
+                            text: option.text.clone(),
+                            next: self.script.target(entry),
+                            condition: option.condition.clone(),
+                        });
+                    }
+                    self.script.add(Action {
+                        kind: NodeKind::Choice,
+                        choices,
+                        ..Default::default()
+                    })
+                }
+                Statement::If(clauses) => {
+                    let mut branches = Vec::with_capacity(clauses.len() + 1);
+                    for (condition, body) in clauses {
+                        let entry = self.block(body, next)?;
+                        branches.push(ChoiceData {
+                            next: self.script.target(entry),
+                            condition: condition.clone(),
+                            ..Default::default()
+                        });
+                    }
+                    // without an `<<else>>` the flow continues after the `<<endif>>`
+                    if branches.last().is_some_and(|b| b.condition.is_some()) {
+                        branches.push(ChoiceData {
+                            next: self.script.target(next),
+                            ..Default::default()
+                        });
+                    }
+                    self.script.add(Action {
+                        kind: NodeKind::Branch,
+                        choices: branches,
+                        ..Default::default()
+                    })
+                }
+            };
+        }
+        Ok(next)
+    }
+
+    /// Adds the speakers of the statements as actors, in order of appearance.
+    fn add_actors(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::Say(Some(speaker), _) => {
+                    let slug = slug(speaker);
+                    if !self.actors.iter().any(|a| a.slug == slug) {
+                        self.actors.push(Actor::new(slug, speaker.as_str()));
+                    }
+                }
+                Statement::Options(options) => {
+                    options.iter().for_each(|o| self.add_actors(&o.body));
+                }
+                Statement::If(clauses) => clauses.iter().for_each(|(_, b)| self.add_actors(b)),
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use rstest::rstest;
+
+    use crate::{
+        prelude::*,
+        tests::{spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    const SCRIPT: &str = r#"
+title: Start
+tags: intro
+---
+// a comment
+Ferris: Hello {$name}! #line:0001
+<<set $gold to 10>>
+<<if $met_bob>>
+    Bob Cat: Hi again.
+<<else>>
+    Bob Cat: Nice to meet you.
+    <<set $met_bob to true>>
+<<endif>>
+-> Buy a sword <<if $rich>>
+    <<set $gold to $gold - 5>>
+    <<jump Shop>>
+-> Leave
+===
+
+title: Shop
+---
+Bob Cat: Thanks!
+===
+"#;
+
+    /// Builds the talk in an app and returns the app and the talk entity.
+    fn build(data: &TalkData) -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, data.fill_builder(TalkBuilder::default()));
+        (app, talk)
+    }
+
+    /// Sends a next request and returns the text of the new current node.
+    fn next_text(app: &mut App, talk: Entity) -> Option<String> {
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        app.world
+            .query_filtered::<&TextNode, With<CurrentNode>>()
+            .get_single(&app.world)
+            .ok()
+            .map(|t| t.0.clone())
+    }
+
+    #[test]
+    fn lines_and_actors() {
+        let data = parse_yarn(SCRIPT).unwrap();
+        assert_eq!(
+            data.actors,
+            vec![
+                Actor::new("ferris", "Ferris"),
+                Actor::new("bob_cat", "Bob Cat")
+            ]
+        );
+
+        let (_, first) = data.script.first().unwrap();
+        assert_eq!(first.text, "Hello {name}!");
+        assert_eq!(first.actors, vec!["ferris".to_string()]);
+        let set = &data.script[&first.next.unwrap()];
+        assert_eq!(
+            set.kind,
+            NodeKind::Set("gold".to_string(), TalkValue::Int(10))
+        );
+    }
+
+    #[test]
+    fn if_is_a_branch() {
+        let data = parse_yarn(SCRIPT).unwrap();
+        let branch = data
+            .script
+            .values()
+            .find(|a| a.kind == NodeKind::Branch)
+            .unwrap();
+        assert_eq!(branch.choices.len(), 2);
+        assert_eq!(branch.choices[0].condition, Some("met_bob".to_string()));
+        assert_eq!(branch.choices[1].condition, None);
+        assert_eq!(data.script[&branch.choices[0].next].text, "Hi again.");
+    }
+
+    #[test]
+    fn options_with_conditions_and_jumps() {
+        let data = parse_yarn(SCRIPT).unwrap();
+        let choice = data
+            .script
+            .values()
+            .find(|a| a.kind == NodeKind::Choice)
+            .unwrap();
+        assert_eq!(choice.choices[0].text, "Buy a sword");
+        assert_eq!(choice.choices[0].condition, Some("rich".to_string()));
+        assert_eq!(choice.choices[1].text, "Leave");
+        assert_eq!(data.script[&choice.choices[1].next].kind, NodeKind::Empty);
+
+        let increment = &data.script[&choice.choices[0].next];
+        assert_eq!(
+            increment.kind,
+            NodeKind::Increment("gold".to_string(), TalkValue::Int(-5))
+        );
+        assert_eq!(data.script[&increment.next.unwrap()].text, "Thanks!");
+    }
+
+    #[test]
+    fn the_talk_starts_from_the_start_node() {
+        let script = "title: Other\n---\nOther\n===\ntitle: Start\n---\nFirst\n<<jump Other>>\n===";
+        let data = parse_yarn(script).unwrap();
+        let (_, first) = data.script.first().unwrap();
+        assert_eq!(first.text, "First");
+        assert_eq!(data.script[&first.next.unwrap()].text, "Other");
+    }
+
+    #[test]
+    fn nodes_starting_with_a_jump() {
+        let script =
+            "title: A\n---\n<<jump B>>\n===\ntitle: B\n---\nHello\n<<stop>>\nUnreachable\n===";
+        let data = parse_yarn(script).unwrap();
+        let (_, first) = data.script.first().unwrap();
+        assert_eq!(first.text, "Hello");
+        assert_eq!(first.next, None);
+    }
+
+    #[test]
+    fn parse_the_example_asset() {
+        let data = parse_yarn(include_str!("../../assets/talks/hello.yarn")).unwrap();
+        assert_eq!(data.actors.len(), 2);
+        assert_eq!(data.script.first().unwrap().1.text, "Hello!");
+    }
+
+    #[test]
+    fn empty_file() {
+        let data = parse_yarn("// nothing here\n").unwrap();
+        assert!(data.script.is_empty());
+    }
+
+    #[test]
+    fn traverse_a_yarn_talk() {
+        let data = parse_yarn(SCRIPT).unwrap();
+        let (mut app, talk) = build(&data);
+
+        assert_eq!(next_text(&mut app, talk).unwrap(), "Hello {name}!");
+        assert_eq!(next_text(&mut app, talk).unwrap(), "Nice to meet you.");
+        let talk_vars = app.world.get::<TalkVariables>(talk).unwrap();
+        assert_eq!(talk_vars.0["gold"], TalkValue::Int(10));
+    }
+
+    #[rstest]
+    #[case("title: A\n---\n<<wait 2>>\n===", YarnLoaderError::UnsupportedCommand(3, "wait".into()))]
+    #[case("title: A\n---\n<<if $gold > 5>>\n<<endif>>\n===", YarnLoaderError::UnsupportedExpression(3, "$gold > 5".into()))]
+    #[case("title: A\n---\n<<set $gold to $other * 2>>\n===", YarnLoaderError::UnsupportedExpression(3, "$gold to $other * 2".into()))]
+    #[case("title: A\n---\n-> Go <<wait 2>>\n===", YarnLoaderError::UnsupportedCommand(3, "wait".into()))]
+    #[case("title: A\n---\nHi <<if $x>>\n===", YarnLoaderError::UnexpectedLine(3, "Hi <<if $x>>".into()))]
+    #[case("title: A\n---\n<<jump B>>\n===", YarnLoaderError::UnknownNode(3, "B".into()))]
+    #[case("title: A\n---\n<<if $x>>\nHi\n===", YarnLoaderError::UnclosedIf(3))]
+    #[case("title: A\n---\n<<else>>\n===", YarnLoaderError::UnexpectedLine(3, "<<else>>".into()))]
+    #[case("tags: a\n---\nHi\n===", YarnLoaderError::MissingTitle(1))]
+    #[case("title: A\n---\nHi\n", YarnLoaderError::UnclosedNode(1))]
+    #[case("title: A\n---\nHi\n===\ntitle: A\n---\nHi\n===", YarnLoaderError::DuplicateNode(5, "A".into()))]
+    #[case("title: A\n---\n<<jump B>>\n===\ntitle: B\n---\n<<jump A>>\n===", YarnLoaderError::EndlessJump("A".into()))]
+    fn unsupported_constructs_are_errors(#[case] script: &str, #[case] error: YarnLoaderError) {
+        let err = parse_yarn(script).unwrap_err();
+        assert_eq!(format!("{err:?}"), format!("{error:?}"));
+    }
+}
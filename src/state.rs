@@ -0,0 +1,347 @@
+//! Save and restore the progress of the talks.
+
+use bevy::{ecs::system::Command, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    BuildNodeId, CurrentNode, EntryPoint, NodeId, StartNode, Talk, TalkHistory, TalkStateError,
+    TalkVariables, VisitedNode,
+};
+
+/// A serializable snapshot of the progress of a talk.
+///
+/// The nodes are identified by their [`NodeId`], so the state can be restored in a talk
+/// built again from the same data (in another game session for example).
+/// The nodes built from talk.ron files use the action ids, while with the builder you can set them with
+/// [`crate::prelude::TalkBuilder::with_id`] (the generated ones are different each time).
+///
+/// The [`crate::prelude::GlobalTalkVariables`] are not part of the state, since they are shared by all the talks.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn save(world: &mut World) {
+///     let talk = world.query_filtered::<Entity, With<Talk>>().single(world);
+///     let state = TalkState::capture(world, talk).unwrap();
+///     // serialize the state with your save system...
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TalkState {
+    /// The id of the current node. `None` if the talk is in a start node.
+    pub current_node: Option<BuildNodeId>,
+    /// The entry point of the start node the talk is in. `None` for the default start node,
+    /// or if the talk is not in a start node.
+    #[serde(default)]
+    pub entry_point: Option<String>,
+    /// If the talk has started.
+    pub has_started: bool,
+    /// The ids of the nodes that have been the current node, sorted.
+    pub visited: Vec<BuildNodeId>,
+    /// The variables of the talk.
+    pub variables: TalkVariables,
+}
+
+impl TalkState {
+    /// Takes a snapshot of the progress of the given talk.
+    ///
+    /// # Errors
+    /// If the entity is not a built talk or the current node has no [`NodeId`].
+    pub fn capture(world: &World, talk: Entity) -> Result<Self, TalkStateError> {
+        let has_started = world
+            .get::<Talk>(talk)
+            .ok_or(TalkStateError::NoTalk(talk))?
+            .has_started;
+
+        let mut current_node = Err(TalkStateError::NoCurrentNode);
+        let mut entry_point = None;
+        let mut visited = vec![];
+        for &node in talk_nodes(world, talk) {
+            let node = world.entity(node);
+            let id = node.get::<NodeId>().map(|id| id.0.clone());
+
+            if node.contains::<CurrentNode>() {
+                current_node = match node.contains::<StartNode>() {
+                    true => Ok(None),
+                    false => id.clone().map(Some).ok_or(TalkStateError::UnidentifiedNode),
+                };
+                entry_point = node.get::<EntryPoint>().map(|e| e.0.clone());
+            }
+            if let (true, Some(id)) = (node.contains::<VisitedNode>(), id) {
+                visited.push(id);
+            }
+        }
+        visited.sort();
+
+        Ok(Self {
+            current_node: current_node?,
+            entry_point,
+            has_started,
+            visited,
+            variables: world
+                .get::<TalkVariables>(talk)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Restores the snapshot in the given talk, built from the same data of the saved one.
+    ///
+    /// It moves the `CurrentNode`, marks the visited nodes and sets `has_started` and the variables.
+    /// The [`TalkHistory`] is not part of the state, so it is cleared.
+    /// No event is sent. Send a `RefireNodeRequest` to get the events of the restored current node.
+    ///
+    /// # Errors
+    /// If the entity is not a built talk or one of the saved nodes is not in the talk.
+    /// In that case the talk is left untouched.
+    pub fn restore(&self, world: &mut World, talk: Entity) -> Result<(), TalkStateError> {
+        if world.get::<Talk>(talk).is_none() {
+            return Err(TalkStateError::NoTalk(talk));
+        }
+
+        let nodes = talk_nodes(world, talk).to_vec();
+        let ids = nodes
+            .iter()
+            .filter_map(|&n| world.get::<NodeId>(n).map(|id| (id.0.clone(), n)))
+            .collect::<HashMap<_, _>>();
+        let find = |id: &BuildNodeId| {
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| TalkStateError::UnknownNode(id.clone()))
+        };
+
+        let current = nodes
+            .iter()
+            .copied()
+            .find(|&n| world.entity(n).contains::<CurrentNode>())
+            .ok_or(TalkStateError::NoCurrentNode)?;
+        let restored_current = match &self.current_node {
+            Some(id) => find(id)?,
+            // the start node of the saved entry point, or the default one
+            None => (nodes.iter().copied())
+                .find(|&n| {
+                    let node = world.entity(n);
+                    let entry = node.get::<EntryPoint>().map(|e| &e.0);
+                    node.contains::<StartNode>() && entry == self.entry_point.as_ref()
+                })
+                .ok_or_else(|| match &self.entry_point {
+                    Some(entry) => TalkStateError::UnknownEntryPoint(entry.clone()),
+                    None => TalkStateError::NoCurrentNode,
+                })?,
+        };
+        let visited = self
+            .visited
+            .iter()
+            .map(find)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // everything is validated, now we can change the talk
+        for node in nodes {
+            world.entity_mut(node).remove::<VisitedNode>();
+        }
+        for node in visited {
+            world.entity_mut(node).insert(VisitedNode);
+        }
+        world.entity_mut(current).remove::<CurrentNode>();
+        world.entity_mut(restored_current).insert(CurrentNode);
+
+        let mut talk = world.entity_mut(talk);
+        talk.insert((self.variables.clone(), TalkHistory::default()));
+        let mut talk = talk.get_mut::<Talk>().expect("Talk");
+        talk.has_started = self.has_started;
+        talk.arrive_at(restored_current);
+        Ok(())
+    }
+}
+
+/// The node entities of a talk (the children of the talk entity).
+fn talk_nodes(world: &World, talk: Entity) -> &[Entity] {
+    world.get::<Children>(talk).map_or(&[], |c| &**c)
+}
+
+/// The command that restores a [`TalkState`] in a talk.
+/// Errors are logged.
+pub(crate) struct RestoreTalkCommand {
+    /// The talk parent entity.
+    pub(crate) talk: Entity,
+    /// The state to restore.
+    pub(crate) state: TalkState,
+}
+
+impl Command for RestoreTalkCommand {
+    fn apply(self, world: &mut World) {
+        if let Err(err) = self.state.restore(world, self.talk) {
+            error!("Cannot restore the state of talk {:?}: {err}", self.talk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+
+    use crate::{
+        prelude::*,
+        tests::{setup_and_next, single, spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// A talk with stable ids: `start --> a --> set gold --> b --> c`.
+    fn builder() -> TalkBuilder {
+        TalkBuilder::default()
+            .say("a")
+            .with_id("a")
+            .set("gold", 5)
+            .say("b")
+            .with_id("b")
+            .say("c")
+            .with_id("c")
+    }
+
+    /// Builds the talk and returns the app and the talk entity.
+    fn setup(state: Option<TalkState>) -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, builder());
+        if let Some(state) = state {
+            RestoreTalkCommand { talk, state }.apply(&mut app.world);
+        }
+        (app, talk)
+    }
+
+    #[test]
+    fn capture_at_start() {
+        let (app, talk) = setup(None);
+        let state = TalkState::capture(&app.world, talk).unwrap();
+        assert_eq!(state, TalkState::default());
+    }
+
+    #[test]
+    fn capture_not_a_talk() {
+        let mut app = talks_minimal_app();
+        let e = app.world.spawn_empty().id();
+        assert_eq!(
+            TalkState::capture(&app.world, e),
+            Err(TalkStateError::NoTalk(e))
+        );
+    }
+
+    #[test]
+    fn restore_on_a_despawned_talk() {
+        let (mut app, talk) = setup(None);
+        app.world.despawn(talk);
+
+        assert_eq!(
+            TalkState::default().restore(&mut app.world, talk),
+            Err(TalkStateError::NoTalk(talk))
+        );
+        // the command logs the error
+        RestoreTalkCommand {
+            talk,
+            state: TalkState::default(),
+        }
+        .apply(&mut app.world);
+    }
+
+    #[test]
+    fn capture_uses_action_ids() {
+        let script = indexmap! {
+            3 => Action { text: "Hello".to_string(), ..default() },
+        };
+        let mut app = setup_and_next(&TalkData::new(script, vec![]));
+        let (talk, _) = single::<(Entity, With<Talk>)>(&mut app.world);
+
+        let state = TalkState::capture(&app.world, talk).unwrap();
+        assert_eq!(state.current_node, Some("3".to_string()));
+        assert_eq!(state.visited, vec!["3".to_string()]);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let (mut app, talk) = setup(None);
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let state = TalkState::capture(&app.world, talk).unwrap();
+        assert_eq!(state.current_node, Some("b".to_string()));
+        assert!(state.has_started);
+        assert_eq!(state.visited, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(state.variables.0["gold"], TalkValue::Int(5));
+
+        // a new game session
+        let saved = serde_ron::to_string(&state).unwrap();
+        let loaded: TalkState = serde_ron::from_str(&saved).unwrap();
+        let (mut app, talk) = setup(Some(loaded));
+
+        assert_eq!(TalkState::capture(&app.world, talk).unwrap(), state);
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "b");
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "c");
+    }
+
+    #[test]
+    fn state_at_an_entry_point_round_trip() {
+        let builder = || {
+            builder().entry_point(
+                "after_quest",
+                TalkBuilder::default().say("Thanks!").with_id("thanks"),
+            )
+        };
+        let mut app = talks_minimal_app();
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder())
+            .at("after_quest")
+            .apply(&mut app.world);
+        let state = TalkState::capture(&app.world, talk).unwrap();
+        assert_eq!(state.current_node, None);
+        assert_eq!(state.entry_point.as_deref(), Some("after_quest"));
+
+        // restored in a talk spawned at the default start
+        let talk = spawn_built_talk(&mut app, builder());
+        RestoreTalkCommand {
+            talk,
+            state: state.clone(),
+        }
+        .apply(&mut app.world);
+        assert_eq!(TalkState::capture(&app.world, talk).unwrap(), state);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let current = app.world.get::<Talk>(talk).unwrap().current_node().unwrap();
+        assert_eq!(app.world.get::<TextNode>(current).unwrap().0, "Thanks!");
+
+        // a talk without the entry point is left untouched
+        let other = spawn_built_talk(&mut app, super::tests::builder());
+        assert_eq!(
+            state.restore(&mut app.world, other),
+            Err(TalkStateError::UnknownEntryPoint("after_quest".to_string()))
+        );
+    }
+
+    #[test]
+    fn restore_unknown_node_leaves_talk_untouched() {
+        let (mut app, talk) = setup(None);
+        let state = TalkState {
+            current_node: Some("a".to_string()),
+            visited: vec!["missing".to_string()],
+            ..default()
+        };
+
+        assert_eq!(
+            state.restore(&mut app.world, talk),
+            Err(TalkStateError::UnknownNode("missing".to_string()))
+        );
+        assert_eq!(
+            TalkState::capture(&app.world, talk).unwrap(),
+            TalkState::default()
+        );
+    }
+}
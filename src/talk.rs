@@ -3,7 +3,7 @@
 use aery::prelude::*;
 use bevy::prelude::*;
 
-use crate::builder::TalkBuilder;
+use crate::{builder::TalkBuilder, conditions::ConditionId};
 
 /// The relationship of the dialogue nodes.
 /// It needs to be Poly because the choice nodes can have multiple branches.
@@ -24,9 +24,29 @@ pub struct Talk {
     /// Helper field to know if the talk has started.
     /// You can also check if the child `CurrentNode` has the `StartNode` component.
     pub has_started: bool,
+    /// The node entity with the `CurrentNode` component. It is `None` until the graph is built.
+    pub(crate) current_node: Option<Entity>,
+    /// The start node the talk was spawned at, where it goes back when reset.
+    pub(crate) start_node: Option<Entity>,
+    /// How many times the talk reached a node, counting the nodes reached again (like a node following itself).
+    pub(crate) arrivals: u32,
 }
 
 impl Talk {
+    /// The current node of the talk, the one with the `CurrentNode` component.
+    /// It is updated right away when the talk moves, while the `CurrentNode` component is moved with commands.
+    ///
+    /// It is `None` until the dialogue graph is built.
+    pub fn current_node(&self) -> Option<Entity> {
+        self.current_node
+    }
+
+    /// Sets the current node, counting the arrival even if the node is the same.
+    pub(crate) fn arrive_at(&mut self, node: Entity) {
+        self.current_node = Some(node);
+        self.arrivals = self.arrivals.wrapping_add(1);
+    }
+
     /// Create a default [`TalkBuilder`].
     pub fn builder() -> TalkBuilder {
         TalkBuilder::default()
@@ -38,10 +58,86 @@ impl Talk {
 #[component(storage = "SparseSet")]
 pub struct CurrentNode;
 
+/// Marker component for the nodes of a Talk that have been the current node at least once.
+#[derive(Component, Default, Debug)]
+#[component(storage = "SparseSet")]
+pub struct VisitedNode;
+
+/// The history of a Talk: the nodes that have been the current node, in order, to go back to them
+/// with a `PreviousNodeRequest`. It is added to the talk entity when the talk is built.
+///
+/// The start nodes are not part of the history. Leaving a choice node, going through set and increment nodes
+/// (unless allowed in the [`crate::TalksSettings`]) or leaving a [`RewindBarrier`] node locks the history:
+/// the talk can't go back to the locked nodes, but they are still in the history (to show them in a backlog UI).
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn backlog(talks: Query<&TalkHistory>, texts: Query<&TextNode>) {
+///     for history in &talks {
+///         for text in history.nodes().iter().filter_map(|&n| texts.get(n).ok()) {
+///             println!("{}", text.0);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct TalkHistory {
+    /// The nodes that have been the current node, the most recent last.
+    nodes: Vec<Entity>,
+    /// How many nodes at the start of the history the talk can't go back to.
+    locked: usize,
+}
+
+impl TalkHistory {
+    /// The nodes that have been the current node before the current one, the most recent last.
+    pub fn nodes(&self) -> &[Entity] {
+        &self.nodes
+    }
+
+    /// True if the talk can go back to the previous node.
+    pub fn can_go_back(&self) -> bool {
+        self.nodes.len() > self.locked
+    }
+
+    /// Adds a node left by the talk. If `lock` is true the talk can't go back to it (and the nodes before).
+    pub(crate) fn push(&mut self, node: Entity, lock: bool) {
+        self.nodes.push(node);
+        if lock {
+            self.locked = self.nodes.len();
+        }
+    }
+
+    /// Removes the previous node to go back to, if it is not locked.
+    pub(crate) fn pop(&mut self) -> Option<Entity> {
+        match self.can_go_back() {
+            true => self.nodes.pop(),
+            false => None,
+        }
+    }
+}
+
+/// Marker component for the nodes with side effects that can't be undone (like the ones triggering custom events).
+/// A talk that leaves the node can't go back to it, or to the nodes before it, with a `PreviousNodeRequest`.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct RewindBarrier;
+
 /// Mark a dialogue node as a starting node.
 #[derive(Component, Default, Debug)]
 pub struct StartNode;
 
+/// The name of a named entry point, on its own start node (besides the `StartNode` component).
+///
+/// The talks have one default start node, plus one for each entry point of the builder (or of the talk data).
+/// Use [`crate::prelude::TalkCommandsExt::spawn_talk_at`] to spawn a talk at an entry point.
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct EntryPoint(pub String);
+
 /// Mark a dialogue node as an end node.
 #[derive(Component, Default, Debug)]
 pub struct EndNode;
@@ -56,6 +152,14 @@ pub struct TextNode(pub String);
 #[reflect(Component)]
 pub struct ChoiceNode(pub Vec<Choice>);
 
+/// Component to mark a dialogue node as a branch node containing some conditional branches.
+///
+/// Branch nodes are never the current node: the traversal goes through them automatically,
+/// following the first branch whose condition holds.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct BranchNode(pub Vec<ConditionalBranch>);
+
 /// Component to mark a dialogue node as a join node.
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
@@ -73,6 +177,10 @@ pub struct Choice {
     pub text: String,
     /// The next entity to go to if the choice is selected.
     pub next: Entity,
+    /// The condition that must hold for the choice to be available. If `None` the choice is always available.
+    pub condition: Option<ConditionId>,
+    /// If the choice has been picked before (see [`crate::prelude::ReadHistory`]). Set in the `ChoiceNodeEvent`.
+    pub seen: bool,
 }
 
 impl Choice {
@@ -94,6 +202,17 @@ impl Choice {
         Self {
             text: text.into(),
             next,
+            condition: None,
+            seen: false,
         }
     }
 }
+
+/// The condition and next entity of a branch.
+#[derive(Debug, Reflect, Clone)]
+pub struct ConditionalBranch {
+    /// The condition that must hold to follow the branch. If `None` the branch is always followed.
+    pub condition: Option<ConditionId>,
+    /// The next entity to go to if the branch is followed.
+    pub next: Entity,
+}
@@ -2,9 +2,11 @@
 
 use crate::{
     builder::{BuildNodeId, TalkBuilder},
-    prelude::{Actor, ActorSlug},
+    conditions::ConditionId,
+    prelude::{Actor, ActorSlug, ChoiceTimeout},
+    variables::{TalkValue, VariableName},
 };
-use bevy::{prelude::*, reflect::TypePath, utils::HashMap};
+use bevy::{asset::LoadedUntypedAsset, prelude::*, reflect::TypePath, utils::HashMap};
 use indexmap::IndexMap;
 
 /// A unique identifier for an action in a Talk.
@@ -15,7 +17,7 @@ use indexmap::IndexMap;
 pub(crate) type ActionId = usize;
 
 /// An enumeration of the different kinds of actions that can be performed in a Talk.
-#[derive(Debug, Default, Clone, Hash, Eq, PartialEq, serde::Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum NodeKind {
     /// An entry point of the dialogue graph
     Start,
@@ -24,10 +26,18 @@ pub enum NodeKind {
     Talk,
     /// A choice action, where the user is presented with a choice.
     Choice,
+    /// A branch action, where the first branch with a condition that holds is followed.
+    Branch,
     /// An enter action, where a character enters a scene.
     Join,
     /// An exit action, where a character exits a scene.
     Leave,
+    /// A set action, where a variable is set to the given value.
+    Set(VariableName, TalkValue),
+    /// An increment action, where a numeric variable is incremented by the given amount.
+    Increment(VariableName, TalkValue),
+    /// An empty action, without events. Useful as the end of a choice that doesn't say anything more.
+    Empty,
 }
 
 /// A struct that represents an action in a Talk.
@@ -36,19 +46,44 @@ pub enum NodeKind {
 /// kind of action, the actors involved in the action, any choices that the user can make during
 /// the action, the text of the action, the ID of the next action to perform, whether the action is
 /// the start of the Talk, and any sound effect associated with the action.
-#[derive(Debug, Default, Clone, Eq, Hash, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub(crate) struct Action {
     /// The kind of action.
     pub(crate) kind: NodeKind,
     /// The actors involved in the action.
     pub(crate) actors: Vec<ActorSlug>,
-    /// Any choices that the user can make during the action.
+    /// Any choices that the user can make during the action (or the branches of a branch action).
     pub(crate) choices: Vec<ChoiceData>,
     /// The text of the action.
     pub(crate) text: String,
     /// The ID of the next action to perform.
     pub(crate) next: Option<ActionId>,
+    /// The time limit of the choices and the default choice.
+    pub(crate) timeout: Option<ChoiceTimeout>,
+    /// The delay in seconds to move to the next action automatically.
+    pub(crate) auto_advance: Option<f32>,
+    /// The custom components to add to the node of the action.
+    pub(crate) components: ActionComponents,
 }
+
+/// The reflected custom components of an action (like `NodeEventEmitter` components) or of an actor.
+#[derive(Debug, Default)]
+pub(crate) struct ActionComponents(pub(crate) Vec<Box<dyn Reflect>>);
+
+impl Clone for ActionComponents {
+    fn clone(&self) -> Self {
+        Self(self.0.iter().map(|c| c.clone_value()).collect())
+    }
+}
+
+impl PartialEq for ActionComponents {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && (self.0.iter().zip(other.0.iter()))
+                .all(|(a, b)| a.reflect_partial_eq(&**b).unwrap_or(false))
+    }
+}
+
 /// A struct that represents a choice in a Talk.
 ///
 /// This struct is used to define a choice in a Talk. It contains the text of the choice and
@@ -59,37 +94,82 @@ pub(crate) struct ChoiceData {
     pub(crate) text: String,
     /// The ID of the next action to perform if the choice is selected.
     pub(crate) next: ActionId,
+    /// The condition that must hold for the choice to be available.
+    pub(crate) condition: Option<ConditionId>,
 }
 
 /// The asset representation of a Talk. It is assumed to represent a well formed Talk,
 /// because the loader should have already validated it while loading.
 ///
-#[derive(Asset, Debug, Default, Clone, TypePath)]
+#[derive(Asset, Debug, Default, Clone, PartialEq, TypePath)]
 pub struct TalkData {
     /// The list of actions that make up the Talk.
     pub(crate) script: IndexMap<ActionId, Action>,
     /// The list of actors that appear in the Talk.
     pub(crate) actors: Vec<Actor>,
+    /// The named entry points of the Talk, with the action each one starts from.
+    pub(crate) entry_points: IndexMap<String, ActionId>,
+    /// The custom components of the actors, by actor slug.
+    pub(crate) actor_components: IndexMap<ActorSlug, ActionComponents>,
+    /// The handles of the portraits and voices of the actors, loaded as dependencies of the talk.
+    #[dependency]
+    pub(crate) actor_assets: Vec<Handle<LoadedUntypedAsset>>,
 }
 
 impl TalkData {
+    /// The action the talk starts from: the `Start` action, or the first action if there is none.
+    /// It is `None` only if the script is empty.
+    pub(crate) fn start_action(&self) -> Option<ActionId> {
+        (self.script.iter())
+            .find(|(_, action)| action.kind == NodeKind::Start)
+            .or(self.script.first())
+            .map(|(id, _)| *id)
+    }
+
     /// Creates a new `TalkData` with the given script and actors.
     #[allow(dead_code)]
     pub(crate) fn new(script: IndexMap<ActionId, Action>, actors: Vec<Actor>) -> Self {
-        Self { script, actors }
+        Self {
+            script,
+            actors,
+            entry_points: IndexMap::new(),
+            actor_components: IndexMap::new(),
+            actor_assets: vec![],
+        }
+    }
+
+    /// The actors that appear in the talk, with their portraits and voices.
+    pub fn actors(&self) -> &[Actor] {
+        &self.actors
     }
 
     /// Take a builder and fill it with the talk actions
     pub(crate) fn fill_builder(&self, mut builder: TalkBuilder) -> TalkBuilder {
         builder = builder.add_actors(self.actors.clone());
+        for (slug, components) in &self.actor_components {
+            let components = components.clone().0.into_iter();
+            (builder.actor_components).extend(components.map(|c| (slug.clone(), c)));
+        }
 
         if self.script.is_empty() {
             return builder;
         }
 
         let mut visited = HashMap::with_capacity(self.script.len());
-        let start_id = self.script.keys().next().unwrap();
-        prepare_builder(*start_id, &self.script, builder, &mut visited)
+        let start_id = self.start_action().unwrap();
+        builder = prepare_builder(start_id, &self.script, builder, &mut visited);
+
+        for (name, id) in &self.entry_points {
+            let mut inner_builder = TalkBuilder::default();
+            // if already visited, just connect to it instead of building the actions again
+            if let Some(node) = visited.get(id) {
+                inner_builder = inner_builder.connect_to(node.clone());
+            } else {
+                inner_builder = prepare_builder(*id, &self.script, inner_builder, &mut visited);
+            }
+            builder = builder.entry_point(name.clone(), inner_builder);
+        }
+        builder
     }
 }
 
@@ -104,10 +184,13 @@ fn prepare_builder(
     let mut the_action = &actions[&starting_action_id];
     let mut the_id = starting_action_id;
 
+    // a start action has no node, the actions pointing to it are connected to the node it leads to
+    let mut pending_start = None;
     let mut done = false;
     while !done {
-        match the_action.kind {
-            NodeKind::Start => (), // nothing to do for this as of now
+        match &the_action.kind {
+            NodeKind::Start if pending_start == Some(the_id) => break, // a start pointing to itself
+            NodeKind::Start => pending_start = Some(the_id),
             NodeKind::Talk => {
                 builder = match the_action.actors.len() {
                     0 => builder.say(&the_action.text),
@@ -115,7 +198,7 @@ fn prepare_builder(
                     2.. => builder.actors_say(&the_action.actors, &the_action.text),
                 }
             }
-            NodeKind::Choice => {
+            NodeKind::Choice | NodeKind::Branch => {
                 let mut choice_vec = Vec::with_capacity(the_action.choices.len());
 
                 for c in the_action.choices.iter() {
@@ -129,18 +212,42 @@ fn prepare_builder(
                     } else {
                         inner_builder = prepare_builder(next, actions, inner_builder, visited);
                     }
-                    choice_vec.push((text, inner_builder));
+                    choice_vec.push((text, c.condition.clone(), inner_builder));
                 }
 
-                builder = builder.choose(choice_vec);
+                builder = match the_action.kind {
+                    NodeKind::Branch => builder.branch(
+                        choice_vec
+                            .into_iter()
+                            .map(|(_, condition, inner)| (condition, inner))
+                            .collect(),
+                    ),
+                    _ => builder.choose_if(choice_vec),
+                }
+                .with_id(the_id.to_string());
+                builder = with_action_components(builder, the_action);
                 visited.insert(the_id, builder.last_node_id());
+                if let Some(start) = pending_start {
+                    visited.insert(start, builder.last_node_id());
+                }
                 break; // no other nodes to visit from a choice (nexts are not used in this case)
             }
             NodeKind::Join => builder = builder.join(&the_action.actors),
             NodeKind::Leave => builder = builder.leave(&the_action.actors),
+            NodeKind::Set(name, value) => builder = builder.set(name, value.clone()),
+            NodeKind::Increment(name, amount) => builder = builder.increment(name, amount.clone()),
+            NodeKind::Empty => builder = builder.empty_node(),
         }
 
-        visited.insert(the_id, builder.last_node_id());
+        // the action id is the stable id of the node
+        if the_action.kind != NodeKind::Start {
+            builder = builder.with_id(the_id.to_string());
+            builder = with_action_components(builder, the_action);
+            visited.insert(the_id, builder.last_node_id());
+            if let Some(start) = pending_start.take() {
+                visited.insert(start, builder.last_node_id());
+            }
+        }
         if let Some(next) = the_action.next {
             // just connect if already processed
             if visited.get(&next).is_some() {
@@ -158,6 +265,20 @@ fn prepare_builder(
     builder
 }
 
+/// Add the timeout, the auto advance delay and the custom components of the action to the last node of the builder.
+fn with_action_components(mut builder: TalkBuilder, action: &Action) -> TalkBuilder {
+    if let Some(timeout) = action.timeout {
+        builder = builder.choice_timeout(timeout.seconds, timeout.default_choice);
+    }
+    if let Some(seconds) = action.auto_advance {
+        builder = builder.auto_advance(seconds);
+    }
+    for component in action.components.0.iter() {
+        builder = builder.with_reflect_component(component.clone_value());
+    }
+    builder
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -233,14 +354,36 @@ mod tests {
         assert_on_text_nodes(world, map);
     }
 
+    #[test]
+    fn talk_starts_from_the_start_action() {
+        let script = indexmap! {
+            1 => Action { text: "Later".to_string(), next: Some(2), ..default() },
+            2 => Action { kind: NodeKind::Start, next: Some(3), ..default() },
+            3 => Action { text: "First".to_string(), next: Some(1), ..default() },
+        };
+
+        let mut world = build(TalkData::new(script, vec![]));
+        assert_eq!(count::<&TextNode>(&mut world), 2);
+        let mut nodes = world.query::<(Entity, &TextNode, Relations<FollowedBy>)>();
+        let mut follower = |text: &str| {
+            let (_, _, edges) = nodes.iter(&world).find(|(_, t, _)| t.0 == text).unwrap();
+            edges.targets(FollowedBy)[0]
+        };
+        let first = follower("Later");
+        assert_eq!(world.get::<TextNode>(first).unwrap().0, "First");
+        // the start node leads to the action after the start action
+        let mut start = world.query_filtered::<Relations<FollowedBy>, With<StartNode>>();
+        assert_eq!(start.single(&world).targets(FollowedBy), [first]);
+    }
+
     #[test]
     fn choice_pointing_to_talks() {
         let script = indexmap! {
             0 =>
             Action {
                 choices: vec![
-                    ChoiceData { text: "Choice 1".to_string(), next: 1, },
-                    ChoiceData { text: "Choice 2".to_string(), next: 2, },
+                    ChoiceData { text: "Choice 1".to_string(), next: 1, ..default() },
+                    ChoiceData { text: "Choice 2".to_string(), next: 2, ..default() },
                 ],
                 kind: NodeKind::Choice,
                 ..default()
@@ -259,6 +402,45 @@ mod tests {
         assert_on_choice_nodes(&mut world, map);
     }
 
+    #[test]
+    fn branch_pointing_to_talks() {
+        let script = indexmap! {
+            0 =>
+            Action {
+                choices: vec![
+                    ChoiceData { next: 1, condition: Some("cond".to_string()), ..default() },
+                    ChoiceData { next: 2, ..default() },
+                ],
+                kind: NodeKind::Branch,
+                ..default()
+            },
+            1 => Action { text: "Hello".to_string(), next: Some(2), ..default() },
+            2 => Action { text: "Fin".to_string(), ..default() },
+        };
+
+        let mut world = build(TalkData::new(script, vec![]));
+
+        assert_eq!(count::<&TextNode>(&mut world), 2);
+        assert_eq!(count::<&ChoiceNode>(&mut world), 0);
+        let branch_node = world.query::<&BranchNode>().single(&world);
+        assert_eq!(branch_node.0.len(), 2);
+        assert_eq!(branch_node.0[0].condition, Some("cond".to_string()));
+        assert_eq!(branch_node.0[1].condition, None);
+    }
+
+    #[test]
+    fn set_and_increment_actions() {
+        let script = indexmap! {
+            0 => Action { kind: NodeKind::Set("flag".to_string(), TalkValue::Bool(true)), next: Some(1), ..default() },
+            1 => Action { kind: NodeKind::Increment("count".to_string(), TalkValue::Int(1)), ..default() },
+        };
+
+        let mut world = build(TalkData::new(script, vec![]));
+
+        assert_eq!(count::<&SetNode>(&mut world), 1);
+        assert_eq!(count::<&IncrementNode>(&mut world), 1);
+    }
+
     #[test]
     fn connect_back_from_branch_book_example() {
         // From the Branching and Manual Connections builder section
@@ -268,8 +450,8 @@ mod tests {
             2 =>
             Action {
                 choices: vec![
-                    ChoiceData { text: "Choice 1".to_string(), next: 3, },
-                    ChoiceData { text: "Choice 2".to_string(), next: 4, },
+                    ChoiceData { text: "Choice 1".to_string(), next: 3, ..default() },
+                    ChoiceData { text: "Choice 2".to_string(), next: 4, ..default() },
                 ],
                 kind: NodeKind::Choice,
                 ..default()
@@ -302,8 +484,8 @@ mod tests {
             0 => // entity: 2
             Action {
                 choices: vec![
-                    ChoiceData { text: "First Choice 1".to_string(), next: 1, },
-                    ChoiceData { text: "First Choice 2".to_string(), next: 2, },
+                    ChoiceData { text: "First Choice 1".to_string(), next: 1, ..default() },
+                    ChoiceData { text: "First Choice 2".to_string(), next: 2, ..default() },
                 ],
                 kind: NodeKind::Choice,
                 ..default()
@@ -313,8 +495,8 @@ mod tests {
             3 =>
             Action {
                 choices: vec![
-                    ChoiceData { text: "Second Choice 1".to_string(), next: 2, },
-                    ChoiceData { text: "Second Choice 2".to_string(), next: 4, },
+                    ChoiceData { text: "Second Choice 1".to_string(), next: 2, ..default() },
+                    ChoiceData { text: "Second Choice 2".to_string(), next: 4, ..default() },
                 ],
                 kind: NodeKind::Choice,
                 ..default()
@@ -415,7 +597,7 @@ mod tests {
     fn assert_on_choice_nodes(world: &mut World, map: HashMap<usize, (Vec<u32>, Vec<&str>)>) {
         for (e, t, edges) in world
             .query::<(Entity, &ChoiceNode, Relations<FollowedBy>)>()
-            .iter(&world)
+            .iter(world)
         {
             let eid = e.index() as usize;
             let expected_texts = map[&eid].1.clone();
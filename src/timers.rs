@@ -0,0 +1,460 @@
+//! The nodes advanced automatically after some time and the timed choices, driven by the Bevy `Time`.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{
+        ChoiceNode, ChoiceTimedOutEvent, ChooseNodeRequest, EndNode, NextNodeRequest, Talk,
+        TextNode,
+    },
+    traverse::AutoTraversal,
+};
+
+/// Component for the nodes that move to the next node by themselves after a delay, in seconds.
+///
+/// It's useful for barks and cutscene narration. The delay starts when the node becomes the current node,
+/// then a `NextNodeRequest` is sent for the talk. The talk can still be advanced before with a request.
+/// Choice nodes and end nodes are never advanced automatically (see [`ChoiceTimeout`] for the timed choices).
+///
+/// Add it with [`crate::prelude::TalkBuilder::auto_advance`] or with the `auto_advance` field of the actions in the talk files.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct AutoAdvance(pub f32);
+
+/// Component for the choice nodes with a time limit, in seconds.
+///
+/// The time starts when the node becomes the current node. When it runs out a [`ChoiceTimedOutEvent`] is sent
+/// and the choice at the `default_choice` index (in the `ChoiceNode`) is chosen with a `ChooseNodeRequest`.
+/// If the condition of the default choice doesn't hold, a warning is logged and the choice is left to the player.
+/// The time left is in the [`TalkTimer`] component of the talk, to draw a countdown.
+///
+/// Add it with [`crate::prelude::TalkBuilder::choice_timeout`] or with the `timeout` field of the actions in the talk files.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct ChoiceTimeout {
+    /// The time to choose, in seconds.
+    pub seconds: f32,
+    /// The index of the choice chosen when the time runs out. It indexes all the choices in the `ChoiceNode`,
+    /// not only the available ones.
+    pub default_choice: usize,
+}
+
+/// Optional component for the talk entities to advance the text nodes automatically,
+/// after a reading time computed from the length of the text.
+///
+/// The reading time is `seconds_per_char` times the number of characters of the text, but at least `min_seconds`.
+/// An [`AutoAdvance`] delay on a node takes the precedence. Remove the component to stop the auto mode.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn toggle_auto_mode(
+///     mut commands: Commands,
+///     keys: Res<Input<KeyCode>>,
+///     talks: Query<(Entity, Has<AutoMode>), With<Talk>>,
+/// ) {
+///     if keys.just_pressed(KeyCode::A) {
+///         for (talk, auto) in &talks {
+///             match auto {
+///                 true => commands.entity(talk).remove::<AutoMode>(),
+///                 false => commands.entity(talk).insert(AutoMode::default()),
+///             };
+///         }
+///     }
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AutoMode {
+    /// The minimum time a text stays on screen, in seconds.
+    pub min_seconds: f32,
+    /// The reading time of each character of the text, in seconds.
+    pub seconds_per_char: f32,
+}
+
+impl Default for AutoMode {
+    fn default() -> Self {
+        Self {
+            min_seconds: 1.5,
+            seconds_per_char: 0.05,
+        }
+    }
+}
+
+impl AutoMode {
+    /// The reading time of the text, in seconds.
+    pub fn reading_time(&self, text: &str) -> f32 {
+        (self.seconds_per_char * text.chars().count() as f32).max(self.min_seconds)
+    }
+}
+
+/// Component added to the talks whose current node advances automatically or is a timed choice, with the time left.
+/// UIs can read it to show the progress or a countdown.
+#[derive(Component, Debug, Clone)]
+pub struct TalkTimer {
+    /// The node the timer was started for.
+    node: Entity,
+    /// The arrival at the node the timer was started for, to restart it when the node is reached again.
+    arrival: u32,
+    /// The timer, once it finishes the talk is advanced.
+    timer: Timer,
+}
+
+impl TalkTimer {
+    /// Creates a timer for the node.
+    fn new(node: Entity, arrival: u32, seconds: f32) -> Self {
+        Self {
+            node,
+            arrival,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+
+    /// The node that will be advanced (or whose default choice will be chosen) when the timer finishes.
+    pub fn node(&self) -> Entity {
+        self.node
+    }
+
+    /// The time left before the talk is advanced.
+    pub fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+
+    /// The fraction of the time that has passed, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        self.timer.percent()
+    }
+}
+
+/// The nodes with the components that make them advance by themselves.
+type TimedNodes<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static AutoAdvance>,
+        Option<&'static TextNode>,
+        Option<&'static ChoiceNode>,
+        Option<&'static ChoiceTimeout>,
+        Has<EndNode>,
+    ),
+>;
+
+/// Starts the timers of the talks when they reach a node, even the same node again. When they finish it sends a `NextNodeRequest`,
+/// or a `ChooseNodeRequest` with the default choice and a `ChoiceTimedOutEvent` for the timed choices.
+/// A finished timer starts again, so the request is sent again if the talk didn't move.
+pub(crate) fn advance_timers(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut talks: Query<(Entity, &Talk, Option<&AutoMode>, Option<&mut TalkTimer>)>,
+    nodes: TimedNodes,
+    flow: AutoTraversal,
+    mut next_writer: EventWriter<NextNodeRequest>,
+    mut choose_writer: EventWriter<ChooseNodeRequest>,
+    mut timed_out_writer: EventWriter<ChoiceTimedOutEvent>,
+) {
+    for (talk, talk_comp, auto_mode, timer) in &mut talks {
+        let current = talk_comp.current_node;
+        match timer {
+            Some(mut timer)
+                if Some(timer.node) == current && timer.arrival == talk_comp.arrivals =>
+            {
+                if !timer.timer.tick(time.delta()).just_finished() {
+                    continue;
+                }
+                timer.timer.reset();
+                let node = timer.node;
+                let Ok((_, _, Some(ChoiceNode(choices)), Some(timeout), _)) = nodes.get(node)
+                else {
+                    next_writer.send(NextNodeRequest::new(talk));
+                    continue;
+                };
+                match choices.get(timeout.default_choice) {
+                    Some(choice) if !flow.holds(talk, &choice.condition) => warn!(
+                        "The default choice {} of the timed out node {node:?} is not available",
+                        timeout.default_choice
+                    ),
+                    Some(choice) => {
+                        timed_out_writer.send(ChoiceTimedOutEvent {
+                            talk,
+                            node,
+                            next: choice.next,
+                        });
+                        choose_writer.send(ChooseNodeRequest::new(talk, choice.next));
+                    }
+                    None => error!(
+                        "The default choice {} of the timed out node {node:?} is out of range",
+                        timeout.default_choice
+                    ),
+                }
+            }
+            timer => {
+                let delay = current.and_then(|node| node_delay(&nodes, node, auto_mode));
+                match (current, delay) {
+                    (Some(node), Some(delay)) => {
+                        let timer = TalkTimer::new(node, talk_comp.arrivals, delay);
+                        cmd.entity(talk).insert(timer);
+                    }
+                    _ if timer.is_some() => {
+                        cmd.entity(talk).remove::<TalkTimer>();
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+/// The delay before the node advances automatically (or the time limit of a timed choice), if it does.
+fn node_delay(nodes: &TimedNodes, node: Entity, auto_mode: Option<&AutoMode>) -> Option<f32> {
+    let (auto_advance, text, choice, timeout, is_end) = nodes.get(node).ok()?;
+    if choice.is_some() {
+        return timeout.map(|t| t.seconds);
+    }
+    if is_end {
+        return None;
+    }
+    match (auto_advance, text, auto_mode) {
+        (Some(AutoAdvance(delay)), _, _) => Some(*delay),
+        (None, Some(TextNode(text)), Some(auto_mode)) => Some(auto_mode.reading_time(text)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        prelude::*,
+        tests::{single, spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// Builds the talk and moves it to the first node.
+    fn setup(builder: TalkBuilder) -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, builder);
+        app.world.send_event(NextNodeRequest::new(talk));
+        advance(&mut app, 0.0);
+        (app, talk)
+    }
+
+    /// Advances the time and runs the app.
+    fn advance(app: &mut App, seconds: f32) {
+        let mut time = app.world.resource_mut::<Time>();
+        time.advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    /// The text of the current node.
+    fn current_text(app: &mut App) -> String {
+        let mut current = app.world.query_filtered::<&TextNode, With<CurrentNode>>();
+        current.single(&app.world).0.clone()
+    }
+
+    #[test]
+    fn auto_advance_nodes_move_after_the_delay() {
+        let builder = TalkBuilder::default()
+            .say("Watch out!")
+            .auto_advance(1.0)
+            .say("Too late.");
+        let (mut app, talk) = setup(builder);
+
+        // the timer starts in the frame after the node is reached
+        advance(&mut app, 0.0);
+        advance(&mut app, 0.6);
+        assert_eq!(current_text(&mut app), "Watch out!");
+        let timer = app.world.get::<TalkTimer>(talk).unwrap();
+        assert!((timer.remaining().as_secs_f32() - 0.4).abs() < 1e-6);
+
+        advance(&mut app, 0.6);
+        assert_eq!(current_text(&mut app), "Too late.");
+        // the end node doesn't advance
+        advance(&mut app, 0.0);
+        assert!(app.world.get::<TalkTimer>(talk).is_none());
+    }
+
+    #[test]
+    fn the_timer_restarts_when_the_node_is_reached_again() {
+        let builder = TalkBuilder::default()
+            .say("Tick")
+            .auto_advance(1.0)
+            .say("Tock");
+        let (mut app, talk) = setup(builder);
+        advance(&mut app, 0.0);
+        advance(&mut app, 0.6);
+
+        app.world.send_event(RefireNodeRequest::new(talk));
+        advance(&mut app, 0.0);
+        // the new timer starts in the next frame
+        advance(&mut app, 0.0);
+        let timer = app.world.get::<TalkTimer>(talk).unwrap();
+        assert_eq!(timer.remaining(), Duration::from_secs(1));
+
+        advance(&mut app, 0.6);
+        assert_eq!(current_text(&mut app), "Tick");
+        advance(&mut app, 0.4);
+        assert_eq!(current_text(&mut app), "Tock");
+    }
+
+    #[test]
+    fn auto_mode_advances_the_text_nodes_after_the_reading_time() {
+        let builder = TalkBuilder::default()
+            .say("Hello")
+            .say("A much longer line to read")
+            .say("Bye");
+        let (mut app, talk) = setup(builder);
+        app.world.entity_mut(talk).insert(AutoMode {
+            min_seconds: 1.0,
+            seconds_per_char: 0.1,
+        });
+
+        // a short text stays for the minimum time
+        advance(&mut app, 0.0);
+        advance(&mut app, 1.0);
+        assert_eq!(current_text(&mut app), "A much longer line to read");
+
+        advance(&mut app, 0.0);
+        advance(&mut app, 2.5);
+        assert_eq!(current_text(&mut app), "A much longer line to read");
+        advance(&mut app, 0.2);
+        assert_eq!(current_text(&mut app), "Bye");
+    }
+
+    #[test]
+    fn choice_nodes_never_auto_advance() {
+        let builder = TalkBuilder::default()
+            .say("Pick one")
+            .choose(vec![
+                ("A", TalkBuilder::default().say("a")),
+                ("B", TalkBuilder::default().say("b")),
+            ])
+            .auto_advance(1.0);
+        let (mut app, talk) = setup(builder);
+        app.world.entity_mut(talk).insert(AutoMode::default());
+
+        for _ in 0..3 {
+            advance(&mut app, 10.0);
+        }
+        let (choice_node, _) = single::<(&ChoiceNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(choice_node.0.len(), 2);
+        assert!(app.world.get::<TalkTimer>(talk).is_none());
+        let errors = app.world.resource::<Events<TalkErrorEvent>>();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn reading_time_has_a_minimum() {
+        let auto = AutoMode {
+            min_seconds: 1.0,
+            seconds_per_char: 0.1,
+        };
+        assert_eq!(auto.reading_time("Hi"), 1.0);
+        assert_eq!(auto.reading_time("Hello there!"), 1.2);
+    }
+
+    /// A talk with a timed choice `Pick one --> (A | B)`. Returns the app, the talk and the choice node.
+    fn setup_timed_choice(default_choice: usize) -> (App, Entity, Entity) {
+        let builder = TalkBuilder::default()
+            .say("Pick one")
+            .choose(vec![
+                ("A", TalkBuilder::default().say("a")),
+                ("B", TalkBuilder::default().say("b")),
+            ])
+            .choice_timeout(2.0, default_choice);
+        let (mut app, talk) = setup(builder);
+        app.world.send_event(NextNodeRequest::new(talk));
+        advance(&mut app, 0.0);
+        let node = app.world.get::<Talk>(talk).unwrap().current_node().unwrap();
+        // the timer starts in the frame after the node is reached
+        advance(&mut app, 0.0);
+        (app, talk, node)
+    }
+
+    #[test]
+    fn default_choice_is_chosen_when_the_time_runs_out() {
+        let (mut app, talk, node) = setup_timed_choice(1);
+
+        advance(&mut app, 1.5);
+        let timer = app.world.get::<TalkTimer>(talk).unwrap();
+        assert_eq!(timer.node(), node);
+        assert!((timer.remaining().as_secs_f32() - 0.5).abs() < 1e-6);
+
+        advance(&mut app, 0.6);
+        assert_eq!(current_text(&mut app), "b");
+        let events = app.world.resource::<Events<ChoiceTimedOutEvent>>();
+        let timed_out = events
+            .get_reader()
+            .read(events)
+            .cloned()
+            .collect::<Vec<_>>();
+        let (b, _) = single::<(Entity, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(
+            timed_out,
+            vec![ChoiceTimedOutEvent {
+                talk,
+                node,
+                next: b
+            }]
+        );
+    }
+
+    #[test]
+    fn choosing_in_time_stops_the_timer() {
+        let (mut app, talk, node) = setup_timed_choice(1);
+        let a = app.world.get::<ChoiceNode>(node).unwrap().0[0].next;
+
+        advance(&mut app, 1.0);
+        app.world.send_event(ChooseNodeRequest::new(talk, a));
+        advance(&mut app, 0.0);
+        advance(&mut app, 5.0);
+
+        assert_eq!(current_text(&mut app), "a");
+        assert!(app.world.get::<TalkTimer>(talk).is_none());
+        assert!(app
+            .world
+            .resource::<Events<ChoiceTimedOutEvent>>()
+            .is_empty());
+    }
+
+    #[test]
+    fn unavailable_default_choice_is_not_chosen() {
+        let builder = TalkBuilder::default()
+            .say("Pick one")
+            .choose_if(vec![
+                ("A", None, TalkBuilder::default().say("a")),
+                ("B", Some("never"), TalkBuilder::default().say("b")),
+            ])
+            .choice_timeout(1.0, 1);
+        let (mut app, talk) = setup(builder);
+        app.world.send_event(NextNodeRequest::new(talk));
+        advance(&mut app, 0.0);
+        advance(&mut app, 0.0);
+        advance(&mut app, 1.0);
+        advance(&mut app, 0.0);
+
+        let (choice_node, _) = single::<(&ChoiceNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(choice_node.0.len(), 2);
+        assert!(app
+            .world
+            .resource::<Events<ChoiceTimedOutEvent>>()
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "only to a choice node")]
+    fn timeout_on_a_text_node_panics() {
+        TalkBuilder::default().say("Hello").choice_timeout(1.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn default_choice_out_of_range_panics() {
+        TalkBuilder::default()
+            .choose(vec![("A", TalkBuilder::default().say("a"))])
+            .choice_timeout(1.0, 1);
+    }
+}
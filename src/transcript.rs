@@ -0,0 +1,256 @@
+//! The transcript of the talks, to show a log of the conversation.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{JoinNodeEvent, LeaveNodeEvent, TextNodeEvent};
+
+/// The number of entries kept by the default transcript.
+pub const DEFAULT_TRANSCRIPT_CAPACITY: usize = 100;
+
+/// Optional component for the talk entities that records what happens in the talk, for a conversation log screen.
+///
+/// It records the lines of the text nodes (with the placeholders resolved), the texts of the chosen choices
+/// and the actors joining and leaving, as they happen. When the capacity is reached the oldest entries are dropped.
+/// Refiring a node or going back to the previous node doesn't record its line again.
+///
+/// It is serializable, so it can be saved with the rest of the game (it is not part of the [`crate::prelude::TalkState`]).
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_talks::prelude::*;
+///
+/// fn spawn(mut commands: Commands) {
+///     let builder = TalkBuilder::default().say("Hello").say("Bye");
+///     commands.spawn_talk(builder).insert(TalkTranscript::new(50));
+/// }
+///
+/// fn show_log(transcripts: Query<&TalkTranscript, Changed<TalkTranscript>>) {
+///     for transcript in &transcripts {
+///         for entry in transcript.entries() {
+///             if let TranscriptEntry::Line { actors, text } = entry {
+///                 println!("{}: {text}", actors.join(", "));
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SavedTranscript")]
+pub struct TalkTranscript {
+    /// The maximum number of entries kept.
+    capacity: usize,
+    /// The recorded entries, the oldest first.
+    entries: VecDeque<TranscriptEntry>,
+}
+
+/// The deserialized form of a [`TalkTranscript`], whose entries can be over the capacity.
+#[derive(Deserialize)]
+struct SavedTranscript {
+    /// The maximum number of entries kept.
+    capacity: usize,
+    /// The saved entries, the oldest first.
+    entries: VecDeque<TranscriptEntry>,
+}
+
+impl From<SavedTranscript> for TalkTranscript {
+    fn from(saved: SavedTranscript) -> Self {
+        let SavedTranscript {
+            capacity,
+            mut entries,
+        } = saved;
+        // keep the newest entries, like when pushing them
+        entries.drain(..entries.len().saturating_sub(capacity));
+        Self { capacity, entries }
+    }
+}
+
+/// Something that happened in a talk, recorded in a [`TalkTranscript`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptEntry {
+    /// The line of a text node.
+    Line {
+        /// The names of the actors saying the line.
+        actors: Vec<String>,
+        /// The text, with the placeholders resolved.
+        text: String,
+    },
+    /// The text of the chosen choice.
+    Choice(String),
+    /// The names of the actors joining the conversation.
+    Join(Vec<String>),
+    /// The names of the actors leaving the conversation.
+    Leave(Vec<String>),
+}
+
+impl Default for TalkTranscript {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSCRIPT_CAPACITY)
+    }
+}
+
+impl TalkTranscript {
+    /// Creates an empty transcript that keeps up to `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// The maximum number of entries kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The recorded entries, the oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TranscriptEntry> {
+        self.entries.iter()
+    }
+
+    /// Removes all the entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Adds an entry, dropping the oldest ones over the capacity.
+    pub(crate) fn push(&mut self, entry: TranscriptEntry) {
+        self.entries.push_back(entry);
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Records the event emitted by a node, if it is a text, join or leave event.
+    pub(crate) fn record(&mut self, event: &dyn Reflect) {
+        if let Some(TextNodeEvent { text, actors, .. }) = event.downcast_ref() {
+            self.push(TranscriptEntry::Line {
+                actors: actors.clone(),
+                text: text.clone(),
+            });
+        } else if let Some(JoinNodeEvent { actors }) = event.downcast_ref() {
+            self.push(TranscriptEntry::Join(actors.clone()));
+        } else if let Some(LeaveNodeEvent { actors }) = event.downcast_ref() {
+            self.push(TranscriptEntry::Leave(actors.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        prelude::*,
+        tests::{spawn_built_talk, talks_minimal_app},
+    };
+
+    use super::*;
+
+    /// Builds the talk with a transcript and returns the app and the talk entity.
+    fn setup(builder: TalkBuilder, capacity: usize) -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        let talk = spawn_built_talk(&mut app, builder);
+        app.world
+            .entity_mut(talk)
+            .insert(TalkTranscript::new(capacity));
+        (app, talk)
+    }
+
+    /// Sends the request and runs the app.
+    fn send(app: &mut App, request: impl Event) {
+        app.world.send_event(request);
+        app.update();
+    }
+
+    /// The entries of the transcript of the talk.
+    fn entries(app: &App, talk: Entity) -> Vec<TranscriptEntry> {
+        let transcript = app.world.get::<TalkTranscript>(talk).unwrap();
+        transcript.entries().cloned().collect()
+    }
+
+    #[test]
+    fn lines_choices_and_actors_are_recorded() {
+        let builder = TalkBuilder::default()
+            .add_actor(Actor::new("bob", "Bob"))
+            .join(&["bob".to_string()])
+            .actor_say("bob", "Hi {actor:bob} here")
+            .choose(vec![
+                ("Bye", TalkBuilder::default().leave(&["bob".to_string()])),
+                ("Stay", TalkBuilder::default().say("Ok")),
+            ]);
+        let (mut app, talk) = setup(builder, 10);
+        for _ in 0..3 {
+            send(&mut app, NextNodeRequest::new(talk));
+        }
+        // refires are not recorded
+        send(&mut app, RefireNodeRequest::new(talk));
+        let mut choices = app.world.query::<&ChoiceNode>();
+        let bye = choices.single(&app.world).0[0].next;
+        send(&mut app, ChooseNodeRequest::new(talk, bye));
+
+        let bob = vec!["Bob".to_string()];
+        assert_eq!(
+            entries(&app, talk),
+            vec![
+                TranscriptEntry::Join(bob.clone()),
+                TranscriptEntry::Line {
+                    actors: bob.clone(),
+                    text: "Hi Bob here".to_string()
+                },
+                TranscriptEntry::Choice("Bye".to_string()),
+                TranscriptEntry::Leave(bob),
+            ]
+        );
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped() {
+        let builder = TalkBuilder::default().say("1").say("2").say("3");
+        let (mut app, talk) = setup(builder, 2);
+        for _ in 0..3 {
+            send(&mut app, NextNodeRequest::new(talk));
+        }
+        let texts = entries(&app, talk).into_iter().map(|e| match e {
+            TranscriptEntry::Line { text, .. } => text,
+            _ => unreachable!(),
+        });
+        assert_eq!(texts.collect::<Vec<_>>(), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn transcript_is_serializable() {
+        let mut transcript = TalkTranscript::new(5);
+        transcript.push(TranscriptEntry::Line {
+            actors: vec!["Bob".to_string()],
+            text: "Hello".to_string(),
+        });
+        transcript.push(TranscriptEntry::Choice("Bye".to_string()));
+
+        let saved = serde_ron::to_string(&transcript).unwrap();
+        let loaded: TalkTranscript = serde_ron::from_str(&saved).unwrap();
+        assert_eq!(loaded, transcript);
+    }
+
+    #[test]
+    fn deserialized_transcript_keeps_the_newest_entries() {
+        let saved = r#"(capacity: 2, entries: [Choice("1"), Choice("2"), Choice("3")])"#;
+        let loaded: TalkTranscript = serde_ron::from_str(saved).unwrap();
+        let entries = loaded.entries().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                TranscriptEntry::Choice("2".to_string()),
+                TranscriptEntry::Choice("3".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn huge_capacity_does_not_allocate_upfront() {
+        let transcript = TalkTranscript::new(usize::MAX);
+        assert_eq!(transcript.capacity(), usize::MAX);
+        assert_eq!(transcript.entries().count(), 0);
+    }
+}
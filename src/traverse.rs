@@ -1,10 +1,113 @@
 //! Dialogue graph traversal systems.
 
 use crate::{
-    emit_events, maybe_emit_end_event, maybe_emit_start_event, prelude::*, retrieve_actors,
+    emit_events, events::requests::TalkRequest, maybe_emit_end_event, maybe_emit_start_event,
+    prelude::*, read_history::ReadTracking, retrieve_actors, variables::Variables,
 };
 use aery::{prelude::*, tuple_traits::RelationEntries};
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// System param to go through the nodes that are traversed automatically (branch, set and increment nodes)
+/// and to check the conditions of choices and branches.
+#[derive(SystemParam)]
+pub(crate) struct AutoTraversal<'w, 's> {
+    /// The branch nodes.
+    branch_nodes: Query<'w, 's, &'static BranchNode>,
+    /// The nodes that modify the variables.
+    variable_nodes: Query<
+        'w,
+        's,
+        (Option<&'static SetNode>, Option<&'static IncrementNode>),
+        Or<(With<SetNode>, With<IncrementNode>)>,
+    >,
+    /// The edges of all the nodes.
+    edges: Query<'w, 's, Relations<FollowedBy>>,
+    /// The registered conditions.
+    conditions: Res<'w, TalkConditions>,
+    /// The talk and global variables.
+    variables: Variables<'w, 's>,
+}
+
+impl<'w, 's> AutoTraversal<'w, 's> {
+    /// Checks if the optional condition holds for the given talk.
+    pub(crate) fn holds(&self, talk: Entity, condition: &Option<ConditionId>) -> bool {
+        self.conditions.holds_for(condition, talk, &self.variables)
+    }
+
+    /// Gets the value of the variable as seen by the given talk.
+    pub(crate) fn variable(&self, talk: Entity, name: &str) -> Option<&TalkValue> {
+        self.variables.get(talk, name)
+    }
+
+    /// Goes through the branch, set and increment nodes starting from the given node,
+    /// until a node that has to be the current node is reached.
+    ///
+    /// Branch nodes follow the first branch with a condition that holds,
+    /// set and increment nodes modify the variables and move to their next node.
+    /// It returns the reached node and whether any variable was modified.
+    ///
+    /// If no node can be reached the variables modified on the way are restored,
+    /// so a failed request doesn't change them.
+    fn resolve(&mut self, talk: Entity, node: Entity) -> Result<(Entity, bool), NextActionError> {
+        let mut previous_values = Vec::new();
+        let resolved = self.walk(talk, node, &mut previous_values);
+        if resolved.is_err() {
+            for (name, value) in previous_values.into_iter().rev() {
+                self.variables.restore(talk, &name, value);
+            }
+        }
+        resolved
+    }
+
+    /// Walks the nodes for [`AutoTraversal::resolve`], recording the previous values of the modified variables.
+    /// The variables are modified right away, so the branches after a set node see the new values.
+    fn walk(
+        &mut self,
+        talk: Entity,
+        mut node: Entity,
+        previous_values: &mut Vec<(VariableName, Option<TalkValue>)>,
+    ) -> Result<(Entity, bool), NextActionError> {
+        let mut visited = Vec::new();
+        let mut side_effects = false;
+        loop {
+            // a loop made only of these nodes would never end
+            if visited.contains(&node) {
+                return Err(NextActionError::EndlessLoop(node));
+            }
+            visited.push(node);
+
+            if let Ok(BranchNode(branches)) = self.branch_nodes.get(node) {
+                node = branches
+                    .iter()
+                    .find(|b| {
+                        self.conditions
+                            .holds_for(&b.condition, talk, &self.variables)
+                    })
+                    .map(|b| b.next)
+                    .ok_or(NextActionError::NoAvailableBranch(node))?;
+            } else if let Ok((set, increment)) = self.variable_nodes.get(node) {
+                let names = (set.map(|s| &s.name))
+                    .into_iter()
+                    .chain(increment.map(|i| &i.name));
+                for name in names {
+                    previous_values.push((name.clone(), self.variables.get(talk, name).cloned()));
+                }
+                self.variables.apply(talk, set, increment);
+                side_effects = true;
+
+                let Ok(edges) = self.edges.get(node) else {
+                    return Ok((node, side_effects));
+                };
+                match edges.targets(FollowedBy) {
+                    [] => return Ok((node, side_effects)), // it is an end node
+                    followings => node = validate_next_node(node, followings)?,
+                }
+            } else {
+                return Ok((node, side_effects));
+            }
+        }
+    }
+}
 
 /// Sets the `has_started` field of the `Talk` component to true when a `StartEvent` is received.
 pub(crate) fn set_has_started(mut talks: Query<&mut Talk>, mut start_evs: EventReader<StartEvent>) {
@@ -14,56 +117,193 @@ pub(crate) fn set_has_started(mut talks: Query<&mut Talk>, mut start_evs: EventR
     }
 }
 
+/// System param with everything needed to move the current node of the talks and emit the events of the nodes.
+///
+/// The `CurrentNode` is moved with commands, while the current node in the `Talk` is updated right away,
+/// so multiple requests for the same talk in the same frame are handled correctly.
+#[derive(SystemParam)]
+pub(crate) struct Traversal<'w, 's> {
+    /// The commands to move the current node and emit the events.
+    cmd: Commands<'w, 's>,
+    /// The talks, with their current nodes.
+    talks: Query<'w, 's, &'static mut Talk>,
+    /// The histories of the talks.
+    histories: Query<'w, 's, &'static mut TalkHistory>,
+    /// The transcripts of the talks that have one.
+    transcripts: Query<'w, 's, &'static mut TalkTranscript>,
+    /// The nodes that lock the history when left.
+    barriers: Query<'w, 's, (Has<ChoiceNode>, Has<RewindBarrier>)>,
+    /// The settings, to know which nodes lock the history.
+    settings: Res<'w, TalksSettings>,
+    /// The read history of the nodes and choices.
+    pub(crate) read: ReadTracking<'w, 's>,
+    /// The start nodes.
+    start: Query<'w, 's, Entity, With<StartNode>>,
+    /// The end nodes.
+    end: Query<'w, 's, Entity, With<EndNode>>,
+    /// All the actors.
+    all_actors: Query<'w, 's, &'static Actor>,
+    /// The edges from the nodes to the actors.
+    performers: Query<'w, 's, Relations<PerformedBy>>,
+    /// The node components that emit events.
+    emitters: Query<'w, 's, &'static dyn NodeEventEmitter>,
+    /// To go through the automatic nodes and check the conditions.
+    pub(crate) flow: AutoTraversal<'w, 's>,
+    /// The type registry to send the reflected node events.
+    type_registry: Res<'w, AppTypeRegistry>,
+    /// The start events writer.
+    start_ev_writer: EventWriter<'w, StartEvent>,
+    /// The end events writer.
+    end_ev_writer: EventWriter<'w, EndEvent>,
+}
+
+impl<'w, 's> Traversal<'w, 's> {
+    /// Handles all the requests in order, returning one error for each failed request.
+    pub(crate) fn handle_all<'a, R: TalkRequest>(
+        &mut self,
+        requests: impl Iterator<Item = &'a R>,
+        mut handle: impl FnMut(&mut Self, &R) -> Result<(), NextActionError>,
+    ) -> Vec<TalkErrorEvent> {
+        requests
+            .filter_map(|request| {
+                handle(self, request).err().map(|error| TalkErrorEvent {
+                    talk: request.talk(),
+                    request_kind: R::KIND,
+                    error,
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the current node of the talk.
+    pub(crate) fn current(&self, talk: Entity) -> Result<Entity, NextActionError> {
+        self.talks
+            .get(talk)
+            .ok()
+            .and_then(|t| t.current_node)
+            .ok_or(NextActionError::NoTalk(talk))
+    }
+
+    /// Gets the nodes following the given node.
+    fn followings(&self, node: Entity) -> Vec<Entity> {
+        self.flow
+            .edges
+            .get(node)
+            .map(|edges| edges.targets(FollowedBy).to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Moves the current node of the talk to the given node, going through the automatic nodes,
+    /// and emits the start, end and node events.
+    ///
+    /// The text of the choice that leads to the node, if any, is recorded in the transcript before the node events.
+    fn move_to(
+        &mut self,
+        talk: Entity,
+        next_node: Entity,
+        choice_text: Option<&str>,
+    ) -> Result<(), NextActionError> {
+        let current_node = self.current(talk)?;
+        let (next_node, side_effects) = self.flow.resolve(talk, next_node)?;
+        self.record(talk, current_node, side_effects);
+        if let (Some(text), Ok(mut transcript)) = (choice_text, self.transcripts.get_mut(talk)) {
+            transcript.push(TranscriptEntry::Choice(text.to_string()));
+        }
+
+        // send start event if we are leaving the start node
+        if self.start.get(next_node).is_err() {
+            maybe_emit_start_event(&self.start, current_node, &mut self.start_ev_writer, talk);
+        }
+
+        // send end event if next node is an end node
+        maybe_emit_end_event(&self.end, next_node, &mut self.end_ev_writer, talk);
+
+        // move CurrentNode component to next node
+        move_current(&mut self.cmd, current_node, next_node);
+        self.talks.get_mut(talk).expect("Talk").arrive_at(next_node);
+
+        self.emit(talk, next_node, true);
+        self.read.see_node(talk, next_node);
+        Ok(())
+    }
+
+    /// Adds the node the talk is leaving to its history (unless it is a start node),
+    /// locking the history if the node can't be gone back to.
+    fn record(&mut self, talk: Entity, left_node: Entity, side_effects: bool) {
+        if self.start.contains(left_node) {
+            return;
+        }
+        let Ok(mut history) = self.histories.get_mut(talk) else {
+            return;
+        };
+        let (is_choice, is_barrier) = self.barriers.get(left_node).unwrap_or_default();
+        let lock = is_barrier
+            || (is_choice && !self.settings.rewind_across_choices)
+            || (side_effects && !self.settings.rewind_across_side_effects);
+        history.push(left_node, lock);
+    }
+
+    /// Moves the current node of the talk back to the previous node in its history and emits its events again.
+    fn move_back(&mut self, talk: Entity) -> Result<(), NextActionError> {
+        let current_node = self.current(talk)?;
+        let previous_node = (self.histories.get_mut(talk).ok())
+            .and_then(|mut history| history.pop())
+            .ok_or(NextActionError::NoPreviousNode(current_node))?;
+
+        maybe_emit_end_event(&self.end, previous_node, &mut self.end_ev_writer, talk);
+        move_current(&mut self.cmd, current_node, previous_node);
+        self.talks
+            .get_mut(talk)
+            .expect("Talk")
+            .arrive_at(previous_node);
+
+        self.emit(talk, previous_node, false);
+        Ok(())
+    }
+
+    /// Emits the events of the current node of the talk again, with the start and end events.
+    pub(crate) fn refire(&mut self, talk: Entity) -> Result<(), NextActionError> {
+        let current_node = self.current(talk)?;
+        maybe_emit_start_event(&self.start, current_node, &mut self.start_ev_writer, talk);
+        maybe_emit_end_event(&self.end, current_node, &mut self.end_ev_writer, talk);
+        self.talks
+            .get_mut(talk)
+            .expect("Talk")
+            .arrive_at(current_node);
+        self.emit(talk, current_node, false);
+        Ok(())
+    }
+
+    /// Emits the events of the node, with the actors performing it.
+    /// If `transcribe` is true the events are recorded in the transcript of the talk.
+    fn emit(&mut self, talk: Entity, node: Entity, transcribe: bool) {
+        let actors_in_node = retrieve_actors(&self.performers, node, &self.all_actors);
+        emit_events(
+            &mut self.cmd,
+            &self.emitters,
+            node,
+            &self.type_registry,
+            &self.flow,
+            talk,
+            &self.all_actors,
+            actors_in_node,
+            &self.read,
+            (self.transcripts.get_mut(talk).ok()).filter(|_| transcribe),
+        );
+    }
+}
+
 /// Handles `NextActionRequest` events by moving the current node of the given `Talk` to the next one
 /// and emitting the events in the next node.
 pub(crate) fn next_handler(
-    mut cmd: Commands,
     mut reqs: EventReader<NextNodeRequest>,
-    current_nodes: Query<(Entity, &Parent, Relations<FollowedBy>), With<CurrentNode>>,
-    start: Query<Entity, With<StartNode>>,
-    end: Query<Entity, With<EndNode>>,
-    all_actors: Query<&Actor>,
-    performers: Query<Relations<PerformedBy>>,
-    emitters: Query<&dyn NodeEventEmitter>,
-    type_registry: Res<AppTypeRegistry>,
-    mut start_ev_writer: EventWriter<StartEvent>,
-    mut end_ev_writer: EventWriter<EndEvent>,
-) -> Result<(), NextActionError> {
-    if let Some(event) = reqs.read().next() {
-        for (current_node, talk_parent, edges) in &current_nodes {
-            let this_talk = talk_parent.get();
-            // if this is the talk we want to advance
-            if this_talk == event.talk {
-                // send start event if we are at the start node
-                maybe_emit_start_event(&start, current_node, &mut start_ev_writer, event.talk);
-
-                let followings = edges.targets(FollowedBy);
-
-                let next_node = validate_next_node(followings)?;
-
-                // send end event if next node is an end node
-                maybe_emit_end_event(&end, next_node, &mut end_ev_writer, event.talk);
-
-                // grab the actors in the next node
-                let actors_in_node = retrieve_actors(&performers, next_node, &all_actors);
-                // move CurrentNode component to next node
-                move_current(&mut cmd, current_node, next_node);
-                // emit the events in the next node
-                emit_events(
-                    &mut cmd,
-                    &emitters,
-                    next_node,
-                    &type_registry,
-                    actors_in_node,
-                );
-
-                return Ok(());
-            }
-        }
-
-        return Err(NextActionError::NoTalk);
-    }
-    Ok(())
+    mut traversal: Traversal,
+) -> Vec<TalkErrorEvent> {
+    traversal.handle_all(reqs.read(), |traversal, event| {
+        let current_node = traversal.current(event.talk)?;
+        let next_node = validate_next_node(current_node, &traversal.followings(current_node))?;
+        traversal.move_to(event.talk, next_node, None)
+    })
 }
 
 /// Handles `ChooseActionRequest` events by updating the given Talk graph.
@@ -71,69 +311,134 @@ pub(crate) fn next_handler(
 /// This function is a Bevy system that listens for `ChooseActionRequest` events.
 /// It will move the current node of the given `Talk` to the one selected in the choose event.
 pub(crate) fn choice_handler(
-    mut cmd: Commands,
     mut reqs: EventReader<ChooseNodeRequest>,
-    current_nodes: Query<(Entity, &Parent, Relations<FollowedBy>), With<CurrentNode>>,
-    start: Query<Entity, With<StartNode>>,
-    end: Query<Entity, With<EndNode>>,
-    all_actors: Query<&Actor>,
-    performers: Query<Relations<PerformedBy>>,
-    emitters: Query<&dyn NodeEventEmitter>,
-    type_registry: Res<AppTypeRegistry>,
-    mut start_ev_writer: EventWriter<StartEvent>,
-    mut end_ev_writer: EventWriter<EndEvent>,
-) -> Result<(), NextActionError> {
-    if let Some(event) = reqs.read().next() {
-        for (current_node, talk_parent, edges) in &current_nodes {
-            let this_talk = talk_parent.get();
-            // if this is the talk we want to advance
-            if this_talk == event.talk {
-                // send start event if we are at the start node
-                maybe_emit_start_event(&start, current_node, &mut start_ev_writer, event.talk);
-
-                let followings = edges.targets(FollowedBy);
-
-                let next_node = validate_chosen_node(followings, event.next)?;
-
-                // send end event if next node is an end node
-                maybe_emit_end_event(&end, next_node, &mut end_ev_writer, event.talk);
-
-                // grab the actors in the next node
-                let actors_in_node = retrieve_actors(&performers, next_node, &all_actors);
-                // move CurrentNode component to next node
-                move_current(&mut cmd, current_node, next_node);
-                // emit the events in the next node
-                emit_events(
-                    &mut cmd,
-                    &emitters,
-                    next_node,
-                    &type_registry,
-                    actors_in_node,
-                );
-
-                return Ok(());
+    mut traversal: Traversal,
+    choice_nodes: Query<&ChoiceNode>,
+) -> Vec<TalkErrorEvent> {
+    traversal.handle_all(reqs.read(), |traversal, event| {
+        let current_node = traversal.current(event.talk)?;
+        let next_node = validate_chosen_node(
+            current_node,
+            &traversal.followings(current_node),
+            event.next,
+        )?;
+        validate_choice_condition(
+            &choice_nodes,
+            &traversal.flow,
+            event.talk,
+            current_node,
+            next_node,
+        )?;
+        let choice_text = (choice_nodes.get(current_node).ok())
+            .and_then(|ChoiceNode(choices)| {
+                let mut available = choices.iter().filter(|c| c.next == next_node);
+                available.find(|c| traversal.flow.holds(event.talk, &c.condition))
+            })
+            .map(|c| c.text.clone());
+        traversal.move_to(event.talk, next_node, choice_text.as_deref())?;
+        traversal
+            .read
+            .see_choice(event.talk, current_node, next_node);
+        Ok(())
+    })
+}
+
+/// Handles `JumpToNodeRequest` events by moving the current node of the given `Talk` to the target node,
+/// wherever it is in the graph, and emitting the events in the target node.
+pub(crate) fn jump_handler(
+    mut reqs: EventReader<JumpToNodeRequest>,
+    mut traversal: Traversal,
+    parents: Query<&Parent>,
+    talk_children: Query<&Children, With<Talk>>,
+    node_ids: Query<&NodeId>,
+) -> Vec<TalkErrorEvent> {
+    traversal.handle_all(reqs.read(), |traversal, event| {
+        // only the nodes of the talk are searched, the children of the talk entity
+        let target = match &event.target {
+            JumpTarget::Entity(e) => Some(*e).filter(|&e| {
+                parents
+                    .get(e)
+                    .is_ok_and(|parent| parent.get() == event.talk)
+            }),
+            JumpTarget::Id(id) => (talk_children.get(event.talk).ok()).and_then(|children| {
+                (children.iter().copied())
+                    .find(|&node| node_ids.get(node).is_ok_and(|node_id| &node_id.0 == id))
+            }),
+        };
+        let next_node =
+            target.ok_or_else(|| NextActionError::NodeNotFound(event.target.clone()))?;
+        traversal.move_to(event.talk, next_node, None)
+    })
+}
+
+/// Handles `PreviousNodeRequest` events by moving the current node of the given `Talk` back to the previous node
+/// in its history and emitting the events in that node again.
+pub(crate) fn previous_handler(
+    mut reqs: EventReader<PreviousNodeRequest>,
+    mut traversal: Traversal,
+) -> Vec<TalkErrorEvent> {
+    traversal.handle_all(reqs.read(), |traversal, event| {
+        traversal.move_back(event.talk)
+    })
+}
+
+/// Handles `ResetTalkRequest` events by moving the current node of the given `Talk` back to the start node
+/// it was spawned at.
+///
+/// It also sets `has_started` to false, removes the `VisitedNode` markers, clears the history and sends a `ResetEvent`.
+pub(crate) fn reset_handler(
+    mut cmd: Commands,
+    mut reqs: EventReader<ResetTalkRequest>,
+    mut talks: Query<(&mut Talk, &Children, Option<&mut TalkHistory>)>,
+    mut reset_ev_writer: EventWriter<ResetEvent>,
+) -> Vec<TalkErrorEvent> {
+    let mut errors = vec![];
+    for event in reqs.read() {
+        let Ok((mut talk, children, history)) = talks.get_mut(event.talk) else {
+            errors.push(TalkErrorEvent {
+                talk: event.talk,
+                request_kind: RequestKind::Reset,
+                error: NextActionError::NoTalk(event.talk),
+            });
+            continue;
+        };
+
+        // the markers can be moved by the commands of the requests handled before in this frame,
+        // so the current node is taken from the talk and the visited markers are removed from all the nodes
+        if let Some(current) = talk.current_node {
+            cmd.entity(current).remove::<CurrentNode>();
+        }
+        for &node in children {
+            cmd.entity(node).remove::<VisitedNode>();
+            if talk.start_node == Some(node) {
+                cmd.entity(node).insert(CurrentNode);
+                talk.arrive_at(node);
             }
         }
 
-        return Err(NextActionError::NoTalk);
+        if let Some(mut history) = history {
+            *history = TalkHistory::default();
+        }
+        talk.has_started = false;
+        reset_ev_writer.send(ResetEvent(event.talk));
     }
-    Ok(())
+    errors
 }
 
-/// Moves the `CurrentNode` component from the current node to the next node.
+/// Moves the `CurrentNode` component from the current node to the next node, marking it as visited.
 #[inline]
 fn move_current(cmd: &mut Commands<'_, '_>, current_node: Entity, next_node: Entity) {
     cmd.entity(current_node).remove::<CurrentNode>();
-    cmd.entity(next_node).insert(CurrentNode);
+    cmd.entity(next_node).insert((CurrentNode, VisitedNode));
 }
 
-/// Validates that there is only one next node.
+/// Validates that there is only one node following the given node.
 #[inline]
-fn validate_next_node(followings: &[Entity]) -> Result<Entity, NextActionError> {
+fn validate_next_node(node: Entity, followings: &[Entity]) -> Result<Entity, NextActionError> {
     if followings.len() > 1 {
-        return Err(NextActionError::ChoicesNotHandled);
+        return Err(NextActionError::ChoicesNotHandled(node));
     } else if followings.is_empty() {
-        return Err(NextActionError::NoNextAction);
+        return Err(NextActionError::NoNextAction(node));
     }
 
     Ok(followings[0])
@@ -141,23 +446,52 @@ fn validate_next_node(followings: &[Entity]) -> Result<Entity, NextActionError>
 
 /// Validates that the chosen next node is connected to the current node.
 fn validate_chosen_node(
+    current_node: Entity,
     followings: &[Entity],
     chosen_node: Entity,
 ) -> Result<Entity, NextActionError> {
     if !followings.contains(&chosen_node) {
-        return Err(NextActionError::BadChoice);
+        return Err(NextActionError::BadChoice {
+            node: current_node,
+            chosen: chosen_node,
+        });
     }
 
     Ok(chosen_node)
 }
 
+/// Validates that the condition of the chosen choice (if any) holds.
+fn validate_choice_condition(
+    choice_nodes: &Query<&ChoiceNode>,
+    flow: &AutoTraversal,
+    talk: Entity,
+    current_node: Entity,
+    chosen_node: Entity,
+) -> Result<(), NextActionError> {
+    if let Ok(ChoiceNode(choices)) = choice_nodes.get(current_node) {
+        let available = choices
+            .iter()
+            .filter(|c| c.next == chosen_node)
+            .any(|c| flow.holds(talk, &c.condition));
+        if !available {
+            return Err(NextActionError::UnavailableChoice {
+                node: current_node,
+                chosen: chosen_node,
+            });
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         prelude::Action,
-        tests::{setup_and_next, single},
+        tests::{count, setup_and_next, single, spawn_built_talk, talks_minimal_app},
     };
+    use bevy::ecs::system::Command;
     use indexmap::indexmap;
+    use rstest::rstest;
 
     use super::*;
 
@@ -176,7 +510,7 @@ mod tests {
         };
         let app = setup_and_next(&TalkData::new(script, vec![]));
         let evs = app.world.resource::<Events<TextNodeEvent>>();
-        assert!(evs.len() > 0);
+        assert!(!evs.is_empty());
     }
 
     #[test]
@@ -186,7 +520,7 @@ mod tests {
         };
         let app = setup_and_next(&TalkData::new(script, vec![Actor::new("actor_1", "Actor")]));
         let evs = app.world.resource::<Events<TextNodeEvent>>();
-        assert!(evs.get_reader().read(evs).next().unwrap().actors.len() > 0);
+        assert!(!evs.get_reader().read(evs).next().unwrap().actors.is_empty());
     }
 
     #[test]
@@ -196,8 +530,8 @@ mod tests {
         };
         let app = setup_and_next(&TalkData::new(script, vec![Actor::new("actor_1", "Actor")]));
         let evs = app.world.resource::<Events<JoinNodeEvent>>();
-        assert!(evs.len() > 0);
-        assert!(evs.get_reader().read(evs).next().unwrap().actors.len() > 0);
+        assert!(!evs.is_empty());
+        assert!(!evs.get_reader().read(evs).next().unwrap().actors.is_empty());
     }
 
     #[test]
@@ -207,8 +541,8 @@ mod tests {
         };
         let app = setup_and_next(&TalkData::new(script, vec![Actor::new("actor_1", "Actor")]));
         let evs = app.world.resource::<Events<LeaveNodeEvent>>();
-        assert!(evs.len() > 0);
-        assert!(evs.get_reader().read(evs).next().unwrap().actors.len() > 0);
+        assert!(!evs.is_empty());
+        assert!(!evs.get_reader().read(evs).next().unwrap().actors.is_empty());
     }
 
     #[test]
@@ -218,7 +552,7 @@ mod tests {
         };
         let app = setup_and_next(&TalkData::new(script, vec![]));
         let evs = app.world.resource::<Events<StartEvent>>();
-        assert!(evs.len() > 0);
+        assert!(!evs.is_empty());
     }
 
     #[test]
@@ -228,28 +562,28 @@ mod tests {
         };
         let app = setup_and_next(&TalkData::new(script, vec![]));
         let evs = app.world.resource::<Events<EndEvent>>();
-        assert!(evs.len() > 0);
+        assert!(!evs.is_empty());
     }
 
     #[test]
     fn choice_event_from_choice_node() {
         let script = indexmap! {
             1 => Action { choices: vec![
-                ChoiceData {text: "Choice 1".to_string(), next: 2},
+                ChoiceData {text: "Choice 1".to_string(), next: 2, ..default()},
                 ], kind: NodeKind::Choice, ..default() },
             2 => Action { text: "test".to_string(), ..default() },
         };
         let app = setup_and_next(&TalkData::new(script, vec![]));
         let evs = app.world.resource::<Events<ChoiceNodeEvent>>();
-        assert!(evs.len() > 0);
+        assert!(!evs.is_empty());
     }
 
     #[test]
     fn test_choice_handler() {
         let script = indexmap! {
             1 => Action {  choices: vec![
-                ChoiceData {text: "Choice 1".to_string(), next: 2},
-                ChoiceData {text: "Choice 2".to_string(), next: 3}
+                ChoiceData {text: "Choice 1".to_string(), next: 2, ..default()},
+                ChoiceData {text: "Choice 2".to_string(), next: 3, ..default()}
                 ], kind: NodeKind::Choice, ..default() },
             2 => Action { kind: NodeKind::Leave, ..default() },
             3 => Action { text: "test".to_string(), ..default() },
@@ -287,4 +621,606 @@ mod tests {
         let talk = single::<&Talk>(&mut app.world);
         assert!(talk.has_started);
     }
+
+    #[derive(Resource, Default)]
+    struct QuestDone(bool);
+
+    /// Build a talk from the builder in an app with the `quest_done` condition registered.
+    #[track_caller]
+    fn setup_with_condition(builder: TalkBuilder, quest_done: bool) -> (App, Entity) {
+        let mut app = talks_minimal_app();
+        app.insert_resource(QuestDone(quest_done))
+            .register_talk_condition("quest_done", |q: Res<QuestDone>| q.0);
+        let talk = spawn_built_talk(&mut app, builder);
+        (app, talk)
+    }
+
+    #[rstest]
+    #[case(true, "Thanks!")]
+    #[case(false, "Go find it.")]
+    fn next_follows_first_branch_that_holds(#[case] quest_done: bool, #[case] expected: &str) {
+        let builder = TalkBuilder::default().branch(vec![
+            (Some("quest_done"), TalkBuilder::default().say("Thanks!")),
+            (None, TalkBuilder::default().say("Go find it.")),
+        ]);
+        let (mut app, talk) = setup_with_condition(builder, quest_done);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, expected);
+    }
+
+    #[test]
+    fn next_fails_when_no_branch_holds() {
+        let builder = TalkBuilder::default().branch(vec![(
+            Some("quest_done"),
+            TalkBuilder::default().say("Thanks!"),
+        )]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        assert!(app
+            .world
+            .query_filtered::<Entity, (With<StartNode>, With<CurrentNode>)>()
+            .get_single(&app.world)
+            .is_ok());
+    }
+
+    #[test]
+    fn choice_event_lists_only_available_choices() {
+        let builder = TalkBuilder::default().choose_if(vec![
+            (
+                "Here it is",
+                Some("quest_done"),
+                TalkBuilder::default().say("a"),
+            ),
+            ("Bye", None, TalkBuilder::default().say("b")),
+        ]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let evs = app.world.resource::<Events<ChoiceNodeEvent>>();
+        let choice_event = evs.get_reader().read(evs).next().unwrap().clone();
+        assert_eq!(choice_event.choices.len(), 1);
+        assert_eq!(choice_event.choices[0].text, "Bye");
+    }
+
+    #[test]
+    fn unavailable_choice_cannot_be_chosen() {
+        let builder = TalkBuilder::default().choose_if(vec![
+            (
+                "Here it is",
+                Some("quest_done"),
+                TalkBuilder::default().say("a"),
+            ),
+            ("Bye", None, TalkBuilder::default().say("b")),
+        ]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let (choice_node, _) = single::<(&ChoiceNode, With<CurrentNode>)>(&mut app.world);
+        let locked = choice_node.0[0].next;
+        app.world.send_event(ChooseNodeRequest::new(talk, locked));
+        app.update();
+
+        assert!(app
+            .world
+            .query_filtered::<Entity, (With<ChoiceNode>, With<CurrentNode>)>()
+            .get_single(&app.world)
+            .is_ok());
+    }
+
+    #[test]
+    fn set_and_increment_nodes_are_applied_and_skipped() {
+        let builder = TalkBuilder::default()
+            .set("met_ferris", true)
+            .increment("global.affinity", 2)
+            .say("Hello");
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "Hello");
+        let vars = single::<&TalkVariables>(&mut app.world);
+        assert_eq!(vars.0["met_ferris"], TalkValue::Bool(true));
+        let global = app.world.resource::<GlobalTalkVariables>();
+        assert_eq!(global.0["affinity"], TalkValue::Int(2));
+    }
+
+    #[test]
+    fn failed_request_keeps_the_variables() {
+        let builder = TalkBuilder::default()
+            .set("gold", 5)
+            .increment("global.visits", 1)
+            .branch(vec![(Some("never"), TalkBuilder::default().say("Rich!"))]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+        app.world
+            .get_mut::<TalkVariables>(talk)
+            .unwrap()
+            .0
+            .insert("gold".to_string(), TalkValue::Int(1));
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let errors = app.world.resource::<Events<TalkErrorEvent>>();
+        let error = errors
+            .get_reader()
+            .read(errors)
+            .next()
+            .unwrap()
+            .error
+            .clone();
+        assert!(matches!(error, NextActionError::NoAvailableBranch(_)));
+        let vars = &app.world.get::<TalkVariables>(talk).unwrap().0;
+        assert_eq!(vars.get("gold"), Some(&TalkValue::Int(1)));
+        let globals = &app.world.resource::<GlobalTalkVariables>().0;
+        assert_eq!(globals.get("visits"), None);
+    }
+
+    #[test]
+    fn branch_on_variable() {
+        let builder = TalkBuilder::default().set("met_ferris", true).branch(vec![
+            (Some("met_ferris"), TalkBuilder::default().say("Hi again!")),
+            (None, TalkBuilder::default().say("Nice to meet you.")),
+        ]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "Hi again!");
+    }
+
+    #[test]
+    fn text_event_resolves_variables_and_actors() {
+        let builder = TalkBuilder::default()
+            .add_actor(Actor::new("ferris", "Ferris"))
+            .set("gold", 42)
+            .set("global.town", "Rustville")
+            .actor_say(
+                "ferris",
+                "{actor:ferris} has {gold} coins in {global.town}. {unknown}",
+            );
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        let evs = app.world.resource::<Events<TextNodeEvent>>();
+        let event = evs.get_reader().read(evs).next().unwrap().clone();
+        assert_eq!(event.text, "Ferris has 42 coins in Rustville. {unknown}");
+        assert_eq!(
+            event.raw_text,
+            "{actor:ferris} has {gold} coins in {global.town}. {unknown}"
+        );
+    }
+
+    #[test]
+    fn next_and_reset_in_the_same_frame() {
+        let builder = TalkBuilder::default().say("Hello").say("Bye");
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.world.send_event(ResetTalkRequest::new(talk));
+        app.update();
+        app.update();
+
+        let (current, _) = single::<(Entity, With<CurrentNode>)>(&mut app.world);
+        assert!(app.world.entity(current).contains::<StartNode>());
+        assert!(!app.world.get::<Talk>(talk).unwrap().has_started);
+        assert_eq!(count::<With<VisitedNode>>(&mut app.world), 0);
+    }
+
+    #[test]
+    fn reset_moves_back_to_start() {
+        let builder = TalkBuilder::default()
+            .set("gold", 1)
+            .say("Hello")
+            .say("Bye");
+        let (mut app, talk) = setup_with_condition(builder, false);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        assert!(app.world.get::<Talk>(talk).unwrap().has_started);
+
+        app.world.send_event(ResetTalkRequest::new(talk));
+        app.update();
+
+        let (current, _) = single::<(Entity, With<CurrentNode>)>(&mut app.world);
+        assert!(app.world.entity(current).contains::<StartNode>());
+        assert!(!app.world.get::<Talk>(talk).unwrap().has_started);
+        assert_eq!(count::<With<VisitedNode>>(&mut app.world), 0);
+        assert_eq!(
+            app.world.get::<TalkVariables>(talk).unwrap().0["gold"],
+            TalkValue::Int(1)
+        );
+        let evs = app.world.resource::<Events<ResetEvent>>();
+        assert_eq!(evs.get_reader().read(evs).next().unwrap().0, talk);
+
+        // the talk can be replayed
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "Hello");
+        assert!(app.world.get::<Talk>(talk).unwrap().has_started);
+    }
+
+    #[test]
+    fn reset_moves_back_to_the_entry_point() {
+        let builder = TalkBuilder::default()
+            .say("Nice to meet you!")
+            .entry_point("after_quest", TalkBuilder::default().say("Thanks!"));
+        let mut app = talks_minimal_app();
+        let talk = app.world.spawn(Talk::default()).id();
+        BuildTalkCommand::new(talk, builder)
+            .at("after_quest")
+            .apply(&mut app.world);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "Thanks!");
+
+        app.world.send_event(ResetTalkRequest::new(talk));
+        app.update();
+        let (entry, _) = single::<(&EntryPoint, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(entry.0, "after_quest");
+    }
+
+    /// A talk with stable ids: `start --> a --> b --> c`.
+    fn jump_talk() -> TalkBuilder {
+        TalkBuilder::default()
+            .say("a")
+            .with_id("a")
+            .say("b")
+            .with_id("b")
+            .say("c")
+            .with_id("c")
+    }
+
+    #[test]
+    fn jump_to_node_by_id() {
+        let (mut app, talk) = setup_with_condition(jump_talk(), false);
+
+        app.world.send_event(JumpToNodeRequest::new(talk, "b"));
+        app.update();
+
+        let (text, _) = single::<(&TextNode, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(text.0, "b");
+        assert!(app.world.get::<Talk>(talk).unwrap().has_started);
+        let evs = app.world.resource::<Events<StartEvent>>();
+        assert_eq!(evs.get_reader().read(evs).len(), 1);
+        let evs = app.world.resource::<Events<TextNodeEvent>>();
+        assert_eq!(evs.get_reader().read(evs).next().unwrap().text, "b");
+    }
+
+    #[test]
+    fn jump_backwards_by_entity_and_to_the_end() {
+        let (mut app, talk) = setup_with_condition(jump_talk(), false);
+        app.world.send_event(JumpToNodeRequest::new(talk, "c"));
+        app.update();
+        let evs = app.world.resource::<Events<EndEvent>>();
+        assert_eq!(evs.get_reader().read(evs).len(), 1);
+
+        let a = app
+            .world
+            .query::<(Entity, &NodeId)>()
+            .iter(&app.world)
+            .find(|(_, id)| id.0 == "a")
+            .unwrap()
+            .0;
+        app.world.send_event(JumpToNodeRequest::new(talk, a));
+        app.update();
+
+        let (current, _) = single::<(Entity, With<CurrentNode>)>(&mut app.world);
+        assert_eq!(current, a);
+        let evs = app.world.resource::<Events<StartEvent>>();
+        assert_eq!(evs.get_reader().read(evs).len(), 1);
+    }
+
+    #[test]
+    fn jump_to_node_of_another_talk_fails() {
+        let (mut app, talk) = setup_with_condition(jump_talk(), false);
+        spawn_built_talk(&mut app, TalkBuilder::default().say("x").with_id("x"));
+
+        app.world.send_event(JumpToNodeRequest::new(talk, "x"));
+        app.update();
+
+        let currents = app
+            .world
+            .query_filtered::<&Parent, (With<CurrentNode>, With<StartNode>)>()
+            .iter(&app.world)
+            .map(|p| p.get())
+            .collect::<Vec<_>>();
+        assert!(currents.contains(&talk));
+    }
+
+    /// Spawns a talk `start --> Hello --> Bye` and returns its entity.
+    fn spawn_hello_bye(app: &mut App) -> Entity {
+        spawn_built_talk(app, TalkBuilder::default().say("Hello").say("Bye"))
+    }
+
+    /// The text of the current node of the talk.
+    fn current_text(app: &mut App, talk: Entity) -> String {
+        app.world
+            .query_filtered::<(&TextNode, &Parent), With<CurrentNode>>()
+            .iter(&app.world)
+            .find(|(_, p)| p.get() == talk)
+            .map(|(t, _)| t.0.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn all_requests_in_the_same_frame_are_handled() {
+        let mut app = talks_minimal_app();
+        let talks = (0..3)
+            .map(|_| spawn_hello_bye(&mut app))
+            .collect::<Vec<_>>();
+
+        for talk in &talks {
+            app.world.send_event(NextNodeRequest::new(*talk));
+        }
+        app.update();
+
+        for talk in &talks {
+            assert_eq!(current_text(&mut app, *talk), "Hello");
+        }
+        let evs = app.world.resource::<Events<TextNodeEvent>>();
+        assert_eq!(evs.get_reader().read(evs).len(), 3);
+        let evs = app.world.resource::<Events<StartEvent>>();
+        assert_eq!(evs.get_reader().read(evs).len(), 3);
+    }
+
+    #[test]
+    fn requests_for_the_same_talk_are_handled_in_order() {
+        let mut app = talks_minimal_app();
+        let talk = spawn_hello_bye(&mut app);
+        let other_talk = spawn_hello_bye(&mut app);
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.world.send_event(NextNodeRequest::new(other_talk));
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        assert_eq!(current_text(&mut app, talk), "Bye");
+        assert_eq!(current_text(&mut app, other_talk), "Hello");
+        assert_eq!(count::<With<CurrentNode>>(&mut app.world), 2);
+        for (current, parent) in app
+            .world
+            .query_filtered::<(Entity, &Parent), With<CurrentNode>>()
+            .iter(&app.world)
+        {
+            let talk = app.world.get::<Talk>(parent.get()).unwrap();
+            assert_eq!(talk.current_node(), Some(current));
+        }
+        let evs = app.world.resource::<Events<EndEvent>>();
+        assert_eq!(evs.get_reader().read(evs).len(), 1);
+    }
+
+    /// The error events sent in the app.
+    fn error_events(app: &App) -> Vec<TalkErrorEvent> {
+        let evs = app.world.resource::<Events<TalkErrorEvent>>();
+        evs.get_reader().read(evs).cloned().collect()
+    }
+
+    #[test]
+    fn one_error_per_failed_request() {
+        let mut app = talks_minimal_app();
+        let talk = spawn_hello_bye(&mut app);
+        let not_a_talk = app.world.spawn_empty().id();
+
+        app.world.send_event(NextNodeRequest::new(not_a_talk));
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+
+        assert_eq!(current_text(&mut app, talk), "Bye");
+        let bye = app.world.get::<Talk>(talk).unwrap().current_node().unwrap();
+        assert_eq!(
+            error_events(&app),
+            vec![
+                TalkErrorEvent {
+                    talk: not_a_talk,
+                    request_kind: RequestKind::Next,
+                    error: NextActionError::NoTalk(not_a_talk),
+                },
+                TalkErrorEvent {
+                    talk,
+                    request_kind: RequestKind::Next,
+                    error: NextActionError::NoNextAction(bye),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn choice_errors_carry_the_entities() {
+        let builder = TalkBuilder::default().choose(vec![
+            ("a", TalkBuilder::default().say("a")),
+            ("b", TalkBuilder::default().say("b")),
+        ]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.update();
+        let choice_node = app.world.get::<Talk>(talk).unwrap().current_node().unwrap();
+
+        app.world.send_event(NextNodeRequest::new(talk));
+        app.world.send_event(ChooseNodeRequest::new(talk, talk));
+        app.update();
+
+        assert_eq!(
+            error_events(&app),
+            vec![
+                TalkErrorEvent {
+                    talk,
+                    request_kind: RequestKind::Next,
+                    error: NextActionError::ChoicesNotHandled(choice_node),
+                },
+                TalkErrorEvent {
+                    talk,
+                    request_kind: RequestKind::Choose,
+                    error: NextActionError::BadChoice {
+                        node: choice_node,
+                        chosen: talk
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn error_events_are_sent_with_logging_disabled() {
+        let mut app = talks_minimal_app();
+        app.world.resource_mut::<TalksSettings>().log_errors = false;
+        let not_a_talk = app.world.spawn_empty().id();
+
+        app.world.send_event(ResetTalkRequest::new(not_a_talk));
+        app.world
+            .send_event(JumpToNodeRequest::new(not_a_talk, "a"));
+        app.update();
+
+        let kinds = error_events(&app)
+            .iter()
+            .map(|e| e.request_kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec![RequestKind::Jump, RequestKind::Reset]);
+    }
+
+    #[test]
+    fn previous_goes_back_and_fires_the_events_again() {
+        let mut app = talks_minimal_app();
+        let talk = spawn_hello_bye(&mut app);
+        for _ in 0..2 {
+            app.world.send_event(NextNodeRequest::new(talk));
+            app.update();
+        }
+        let hello = app.world.get::<TalkHistory>(talk).unwrap().nodes().to_vec();
+        assert_eq!(hello.len(), 1);
+
+        app.world.send_event(PreviousNodeRequest::new(talk));
+        app.update();
+        assert_eq!(current_text(&mut app, talk), "Hello");
+        let evs = app.world.resource::<Events<TextNodeEvent>>();
+        let last_text = evs.get_reader().read(evs).last().unwrap().text.clone();
+        assert_eq!(last_text, "Hello");
+
+        // the start node is not in the history
+        app.world.send_event(PreviousNodeRequest::new(talk));
+        app.update();
+        assert_eq!(current_text(&mut app, talk), "Hello");
+        assert_eq!(
+            error_events(&app),
+            vec![TalkErrorEvent {
+                talk,
+                request_kind: RequestKind::Previous,
+                error: NextActionError::NoPreviousNode(hello[0]),
+            }]
+        );
+    }
+
+    #[test]
+    fn reset_clears_the_history() {
+        let mut app = talks_minimal_app();
+        let talk = spawn_hello_bye(&mut app);
+        for _ in 0..2 {
+            app.world.send_event(NextNodeRequest::new(talk));
+            app.update();
+        }
+        app.world.send_event(ResetTalkRequest::new(talk));
+        app.update();
+        assert_eq!(
+            app.world.get::<TalkHistory>(talk),
+            Some(&TalkHistory::default())
+        );
+    }
+
+    #[rstest]
+    #[case(false, "a")]
+    #[case(true, "")] // back to the choice node, without text
+    fn previous_across_choices(#[case] allowed: bool, #[case] expected: &str) {
+        let builder = TalkBuilder::default().say("Pick one").choose(vec![
+            ("A", TalkBuilder::default().say("a")),
+            ("B", TalkBuilder::default().say("b")),
+        ]);
+        let (mut app, talk) = setup_with_condition(builder, false);
+        app.world
+            .resource_mut::<TalksSettings>()
+            .rewind_across_choices = allowed;
+        for _ in 0..2 {
+            app.world.send_event(NextNodeRequest::new(talk));
+            app.update();
+        }
+        let (choice_node, _) = single::<(&ChoiceNode, With<CurrentNode>)>(&mut app.world);
+        let a = choice_node.0[0].next;
+        app.world.send_event(ChooseNodeRequest::new(talk, a));
+        app.update();
+
+        app.world.send_event(PreviousNodeRequest::new(talk));
+        app.update();
+        assert_eq!(current_text(&mut app, talk), expected);
+        assert_eq!(error_events(&app).is_empty(), allowed);
+        let choice_events = app.world.resource::<Events<ChoiceNodeEvent>>();
+        assert_eq!(
+            choice_events.get_reader().read(choice_events).len(),
+            usize::from(allowed)
+        );
+    }
+
+    #[rstest]
+    #[case(false, "b")]
+    #[case(true, "a")]
+    fn previous_across_side_effects(#[case] allowed: bool, #[case] expected: &str) {
+        let builder = TalkBuilder::default().say("a").set("gold", 5).say("b");
+        let (mut app, talk) = setup_with_condition(builder, false);
+        app.world
+            .resource_mut::<TalksSettings>()
+            .rewind_across_side_effects = allowed;
+        for _ in 0..2 {
+            app.world.send_event(NextNodeRequest::new(talk));
+            app.update();
+        }
+
+        app.world.send_event(PreviousNodeRequest::new(talk));
+        app.update();
+        assert_eq!(current_text(&mut app, talk), expected);
+        let history = app.world.get::<TalkHistory>(talk).unwrap();
+        assert_eq!(history.nodes().len(), usize::from(!allowed));
+        assert!(!history.can_go_back());
+    }
+
+    #[test]
+    fn previous_stops_at_rewind_barriers() {
+        let builder = TalkBuilder::default()
+            .say("a")
+            .say("b")
+            .with_component(RewindBarrier)
+            .say("c");
+        let (mut app, talk) = setup_with_condition(builder, false);
+        for _ in 0..3 {
+            app.world.send_event(NextNodeRequest::new(talk));
+            app.update();
+        }
+
+        for _ in 0..2 {
+            app.world.send_event(PreviousNodeRequest::new(talk));
+            app.update();
+        }
+        assert_eq!(current_text(&mut app, talk), "c");
+        assert_eq!(error_events(&app).len(), 2);
+    }
 }
@@ -0,0 +1,268 @@
+//! Variables that the dialogue graphs can read and write.
+
+use std::fmt;
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+/// The name of a variable.
+///
+/// Names starting with [`GLOBAL_PREFIX`] refer to the [`GlobalTalkVariables`] shared by all talks
+/// (the prefix is not part of the stored key), all the others to the [`TalkVariables`] of the talk.
+pub type VariableName = String;
+
+/// The prefix of the names of the global variables.
+pub const GLOBAL_PREFIX: &str = "global.";
+
+/// A typed value stored in the variables.
+///
+/// In the talk.ron files the values are written as plain RON values: `true`, `42`, `1.5`, `"text"`.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TalkValue {
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    Int(i64),
+    /// A floating point value.
+    Float(f64),
+    /// A string value.
+    String(String),
+}
+
+impl Default for TalkValue {
+    fn default() -> Self {
+        TalkValue::Bool(false)
+    }
+}
+
+impl TalkValue {
+    /// Checks if the value is "truthy": `true`, a non zero number or a non empty string.
+    ///
+    /// It is used when a variable is used as a condition.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            TalkValue::Bool(b) => *b,
+            TalkValue::Int(i) => *i != 0,
+            TalkValue::Float(f) => *f != 0.0,
+            TalkValue::String(s) => !s.is_empty(),
+        }
+    }
+
+    /// Adds the amount to the value. Returns `None` if one of the two is not a number,
+    /// or if the sum of two integers overflows. Adding an integer and a float results in a float.
+    pub fn add(&self, amount: &TalkValue) -> Option<TalkValue> {
+        match (self, amount) {
+            (TalkValue::Int(a), TalkValue::Int(b)) => a.checked_add(*b).map(TalkValue::Int),
+            (TalkValue::Int(a), TalkValue::Float(b)) => Some(TalkValue::Float(*a as f64 + b)),
+            (TalkValue::Float(a), TalkValue::Int(b)) => Some(TalkValue::Float(a + *b as f64)),
+            (TalkValue::Float(a), TalkValue::Float(b)) => Some(TalkValue::Float(a + b)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TalkValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TalkValue::Bool(b) => write!(f, "{b}"),
+            TalkValue::Int(i) => write!(f, "{i}"),
+            TalkValue::Float(x) => write!(f, "{x}"),
+            TalkValue::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl From<bool> for TalkValue {
+    fn from(value: bool) -> Self {
+        TalkValue::Bool(value)
+    }
+}
+
+impl From<i32> for TalkValue {
+    fn from(value: i32) -> Self {
+        TalkValue::Int(value.into())
+    }
+}
+
+impl From<i64> for TalkValue {
+    fn from(value: i64) -> Self {
+        TalkValue::Int(value)
+    }
+}
+
+impl From<f32> for TalkValue {
+    fn from(value: f32) -> Self {
+        TalkValue::Float(value.into())
+    }
+}
+
+impl From<f64> for TalkValue {
+    fn from(value: f64) -> Self {
+        TalkValue::Float(value)
+    }
+}
+
+impl From<&str> for TalkValue {
+    fn from(value: &str) -> Self {
+        TalkValue::String(value.to_string())
+    }
+}
+
+impl From<String> for TalkValue {
+    fn from(value: String) -> Self {
+        TalkValue::String(value)
+    }
+}
+
+/// The variables of a single talk. It is added to the parent entity with the `Talk` component.
+#[derive(Component, Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TalkVariables(pub HashMap<VariableName, TalkValue>);
+
+/// The variables shared by all the talks.
+#[derive(Resource, Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalTalkVariables(pub HashMap<VariableName, TalkValue>);
+
+/// Component for a node that sets a variable when reached.
+///
+/// Like branch nodes, it is never the current node (unless it is the last one):
+/// the traversal applies it and moves to the next node automatically.
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct SetNode {
+    /// The name of the variable to set.
+    pub name: VariableName,
+    /// The value to set.
+    pub value: TalkValue,
+}
+
+/// Component for a node that increments a numeric variable when reached.
+/// A missing variable is considered to be zero.
+///
+/// Like branch nodes, it is never the current node (unless it is the last one):
+/// the traversal applies it and moves to the next node automatically.
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct IncrementNode {
+    /// The name of the variable to increment.
+    pub name: VariableName,
+    /// The amount to add (it can be negative).
+    pub amount: TalkValue,
+}
+
+/// System param to read and write the variables of the talks and the global ones.
+#[derive(SystemParam)]
+pub(crate) struct Variables<'w, 's> {
+    /// The variables of each talk.
+    talks: Query<'w, 's, &'static mut TalkVariables>,
+    /// The global variables.
+    global: ResMut<'w, GlobalTalkVariables>,
+}
+
+impl<'w, 's> Variables<'w, 's> {
+    /// Gets the value of the variable as seen by the given talk.
+    pub(crate) fn get(&self, talk: Entity, name: &str) -> Option<&TalkValue> {
+        match name.strip_prefix(GLOBAL_PREFIX) {
+            Some(global_name) => self.global.0.get(global_name),
+            None => self.talks.get(talk).ok()?.0.get(name),
+        }
+    }
+
+    /// Sets the value of the variable as seen by the given talk.
+    pub(crate) fn set(&mut self, talk: Entity, name: &str, value: TalkValue) {
+        match name.strip_prefix(GLOBAL_PREFIX) {
+            Some(global_name) => {
+                self.global.0.insert(global_name.to_string(), value);
+            }
+            None => match self.talks.get_mut(talk) {
+                Ok(mut vars) => {
+                    vars.0.insert(name.to_string(), value);
+                }
+                Err(_) => warn!("Talk {talk:?} has no TalkVariables. Cannot set {name}."),
+            },
+        }
+    }
+
+    /// Sets the variable back to a previous value, removing it if it had no value.
+    pub(crate) fn restore(&mut self, talk: Entity, name: &str, value: Option<TalkValue>) {
+        match (value, name.strip_prefix(GLOBAL_PREFIX)) {
+            (Some(value), _) => self.set(talk, name, value),
+            (None, Some(global_name)) => {
+                self.global.0.remove(global_name);
+            }
+            (None, None) => {
+                if let Ok(mut vars) = self.talks.get_mut(talk) {
+                    vars.0.remove(name);
+                }
+            }
+        }
+    }
+
+    /// Applies the set and increment nodes (if any) to the variables of the given talk.
+    pub(crate) fn apply(
+        &mut self,
+        talk: Entity,
+        set: Option<&SetNode>,
+        increment: Option<&IncrementNode>,
+    ) {
+        if let Some(SetNode { name, value }) = set {
+            self.set(talk, name, value.clone());
+        }
+
+        if let Some(IncrementNode { name, amount }) = increment {
+            let current = match self.get(talk, name) {
+                Some(v) => v.clone(),
+                None => TalkValue::Int(0),
+            };
+            match current.add(amount) {
+                Some(v) => self.set(talk, name, v),
+                None => warn!(
+                    "Cannot increment {name} ({current}) by {amount}. Not numbers or out of range."
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(TalkValue::Int(1), TalkValue::Int(2), Some(TalkValue::Int(3)))]
+    #[case(TalkValue::Int(1), TalkValue::Float(0.5), Some(TalkValue::Float(1.5)))]
+    #[case(TalkValue::Float(1.5), TalkValue::Int(-1), Some(TalkValue::Float(0.5)))]
+    #[case(TalkValue::Int(i64::MAX), TalkValue::Int(1), None)]
+    #[case(TalkValue::Int(i64::MIN), TalkValue::Int(-1), None)]
+    #[case(TalkValue::Bool(true), TalkValue::Int(1), None)]
+    #[case(TalkValue::String("a".into()), TalkValue::Int(1), None)]
+    fn add_values(#[case] a: TalkValue, #[case] b: TalkValue, #[case] expected: Option<TalkValue>) {
+        assert_eq!(a.add(&b), expected);
+    }
+
+    #[rstest]
+    #[case(TalkValue::Bool(true), true)]
+    #[case(TalkValue::Bool(false), false)]
+    #[case(TalkValue::Int(0), false)]
+    #[case(TalkValue::Float(2.0), true)]
+    #[case(TalkValue::String(String::new()), false)]
+    fn truthy_values(#[case] value: TalkValue, #[case] expected: bool) {
+        assert_eq!(value.is_truthy(), expected);
+    }
+
+    #[test]
+    fn values_from_ron() {
+        let values: Vec<TalkValue> = serde_ron::from_str(r#"[true, 42, 1.5, "hi"]"#).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                TalkValue::Bool(true),
+                TalkValue::Int(42),
+                TalkValue::Float(1.5),
+                TalkValue::String("hi".into())
+            ]
+        );
+    }
+}
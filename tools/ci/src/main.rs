@@ -68,18 +68,22 @@ fn main() {
 
     if what_to_run.contains(Check::CLIPPY) {
         // See if clippy has any complaints.
-        // --all-targets --all-features was removed because Emergence currently has no special
-        // targets or features; please add them back as necessary
-        cmd!(sh, "cargo clippy --workspace -- {CLIPPY_FLAGS...}")
-            .run()
-            .expect("Please fix clippy errors in output above.");
+        cmd!(
+            sh,
+            "cargo clippy --workspace --all-features -- {CLIPPY_FLAGS...}"
+        )
+        .run()
+        .expect("Please fix clippy errors in output above.");
     }
 
     if what_to_run.contains(Check::TEST) {
         // Run tests (except doc tests and without building examples)
-        cmd!(sh, "cargo test --workspace --lib --bins --tests --benches")
-            .run()
-            .expect("Please fix failing tests in output above.");
+        cmd!(
+            sh,
+            "cargo test --workspace --all-features --lib --bins --tests --benches"
+        )
+        .run()
+        .expect("Please fix failing tests in output above.");
     }
 
     if what_to_run.contains(Check::DOC_TEST) {